@@ -14,7 +14,7 @@ use url::Url;
 use crate::apub::{AcceptFollow, Follow};
 use crate::{storage, FederationData};
 
-use super::{generate_activity_id, ActivityError};
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
 
 impl AcceptFollow {
     #[instrument(skip_all)]
@@ -29,6 +29,8 @@ impl AcceptFollow {
             object,
             generate_activity_id(data)?,
         ));
+        save_to_outbox(accept.inner(), actor, false, data).await?;
+
         queue_activity(&accept, actor, vec![inbox], data)
             .await
             .map_err(|e| ActivityError::federation(e, "Failed to send AcceptFollow"))
@@ -48,8 +50,19 @@ impl ActivityHandler for AcceptFollow {
         self.actor.inner()
     }
 
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+    /// Verifies that the follow is ours and that it's accepted by the followed account.
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.object.inner(),
+            "Only the followed account can accept the follow",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Accept activity is not hosted by the actor",
+        )?;
+        verification::local_account(self.object.actor.inner(), data).await
     }
 
     #[instrument(name = "receive_accept_follow", skip_all, fields(actor=%self.actor))]
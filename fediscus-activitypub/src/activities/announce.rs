@@ -0,0 +1,50 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::config::Data;
+use activitypub_federation::traits::ActivityHandler;
+use async_trait::async_trait;
+use tracing::instrument;
+use url::Url;
+
+use crate::apub::Announce;
+use crate::FederationData;
+
+use super::{verification, ActivityError};
+
+#[async_trait]
+impl ActivityHandler for Announce {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Announce activity is not hosted by the actor",
+        )
+    }
+
+    #[instrument(name="announce_receive", skip_all, fields(actor=%self.actor.inner(), object=%self.object.inner()))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let account = self
+            .actor
+            .dereference(data)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))?;
+        data.service
+            .repost_post(self.object.inner().clone().into(), &account, self.id.into())
+            .await
+            .map_err(|e| ActivityError::processing(e, "Failed to process announce"))?;
+        Ok(())
+    }
+}
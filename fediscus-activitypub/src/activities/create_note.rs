@@ -3,31 +3,190 @@
 //
 // SPDX-License-Identifier: MIT
 
+use activitypub_federation::activity_queue::queue_activity;
 use activitypub_federation::config::Data;
+use activitypub_federation::fetch::fetch_object_http;
 use activitypub_federation::fetch::object_id::ObjectId;
 use activitypub_federation::kinds::activity::CreateType;
+use activitypub_federation::protocol::context::WithContext;
+use activitypub_federation::protocol::helpers::deserialize_one_or_many;
+use activitypub_federation::protocol::verification::verify_domains_match;
 use activitypub_federation::traits::ActivityHandler;
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
-use tracing::{debug, info, instrument};
+use tracing::{debug, info, instrument, warn};
 use url::Url;
 
 use crate::apub::Note as APubNote;
-use crate::storage::{self, Account, Note};
+use crate::filters::{CommentOrigin, FilterVerdict};
+use crate::jobs;
+use crate::storage::{self, Account, Blog, ModerationPolicy, ModerationState, Note, NoteContent};
 
-use super::ActivityError;
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
+
+/// Returns the blog with the given URL, creating it if we don't know it yet
+pub(crate) async fn blog_for_url(
+    data: &Data<crate::FederationData>,
+    url: &Url,
+) -> Result<Blog, ActivityError> {
+    let storage = data.service.storage();
+    match storage
+        .blog_by_url(url)
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to look up blog"))?
+    {
+        Some(blog) => Ok(blog),
+        None => storage
+            .new_blog(url)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to create new blog")),
+    }
+}
+
+/// Decides whether a new reply by the account is shown right away or held for moderation,
+/// according to the moderation policy of the blog it discusses
+async fn moderation_state_for_reply(
+    data: &Data<crate::FederationData>,
+    account: &Account,
+    blog: &Blog,
+) -> Result<ModerationState, ActivityError> {
+    let approved = match blog.moderation_policy {
+        ModerationPolicy::AutoApprove => true,
+        ModerationPolicy::ApproveKnown => data
+            .service
+            .storage()
+            .has_approved_posts(account.id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to look up account's posts"))?,
+        ModerationPolicy::HoldAll => false,
+    };
+    Ok(if approved {
+        ModerationState::Approved
+    } else {
+        ModerationState::Pending
+    })
+}
 
 impl APubNote {
+    /// Attempts to find the parent note if this is a reply
+    ///
+    /// If the parent is not known yet, its ancestors are fetched from the remote servers until
+    /// a known note or a `#fediscus` root is found and then stored as part of that thread.
+    /// Returns `None` if the thread can't be found, including when an ancestor can't be fetched.
+    pub(super) async fn find_parent_note(
+        &self,
+        data: &Data<crate::FederationData>,
+    ) -> Result<Option<Note>, ActivityError> {
+        let storage = data.service.storage();
+        let max_depth = data.config.federation.max_ancestor_depth;
+
+        // Walk up the chain of unknown ancestors, closest ancestor first
+        let mut ancestors = Vec::new();
+        let mut next = self.in_reply_to.clone();
+        let mut known = None;
+        while let Some(id) = next.take() {
+            known = storage
+                .post_by_uri(&id.inner().clone().into())
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to find parent note"))?;
+            if known.is_some() {
+                break;
+            }
+            if ancestors.len() >= max_depth {
+                debug!("Reached maximum ancestor depth, giving up");
+                return Ok(None);
+            }
+
+            // An ancestor we can't get hold of (gone, unreachable or blocked) means we can't tell
+            // which thread the note belongs to, but that's no reason to fail the whole activity
+            let ancestor = match fetch_object_http::<_, APubNote>(id.inner(), data).await {
+                Ok(response) => response.object,
+                Err(e) => {
+                    warn!("Failed to fetch ancestor note {}: {}", id.inner(), e);
+                    return Ok(None);
+                }
+            };
+            if let Err(e) = verify_domains_match(ancestor.id.inner(), id.inner()) {
+                warn!("Ancestor note {} has a mismatching ID: {}", id.inner(), e);
+                return Ok(None);
+            }
+            next = ancestor.in_reply_to.clone();
+            ancestors.push(ancestor);
+        }
+
+        // Unless we've reached a known note, the top-most ancestor must be a #fediscus root
+        let mut parent = match known {
+            Some(note) => note,
+            None => match ancestors.pop() {
+                Some(root) => {
+                    let account = root.author(data).await?;
+                    match root
+                        .handle_top_level_note(data, &account, CommentOrigin::Imported)
+                        .await?
+                    {
+                        Some(note) => note,
+                        None => return Ok(None),
+                    }
+                }
+                None => return Ok(None),
+            },
+        };
+
+        // Store the remaining ancestors from the top down, each as a reply to the previous one.
+        // A rejected ancestor takes its replies down with it, attaching them to its parent
+        // instead would sneak them past the filters.
+        for ancestor in ancestors.into_iter().rev() {
+            let account = ancestor.author(data).await?;
+            match ancestor
+                .handle_reply_note(data, &account, &parent, CommentOrigin::Imported)
+                .await?
+            {
+                Some(note) => parent = note,
+                None => return Ok(None),
+            }
+        }
+
+        Ok(Some(parent))
+    }
+
+    /// Resolves the author of the note
+    pub(crate) async fn author(
+        &self,
+        data: &Data<crate::FederationData>,
+    ) -> Result<Account, ActivityError> {
+        self.attributed_to
+            .dereference(data)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))
+    }
+
+    /// Processes a note we haven't seen before based on whether it's a reply or top-level note
+    ///
+    /// Returns the stored note, or `None` if the note does not belong to any thread. Ancestors
+    /// fetched along the way are always filtered as imported comments.
+    pub(crate) async fn process_new_note(
+        &self,
+        data: &Data<crate::FederationData>,
+        account: &Account,
+        origin: CommentOrigin,
+    ) -> Result<Option<Note>, ActivityError> {
+        match self.find_parent_note(data).await? {
+            Some(parent) => self.handle_reply_note(data, account, &parent, origin).await,
+            None => self.handle_top_level_note(data, account, origin).await,
+        }
+    }
+
     #[instrument(name="create_note_top_level", skip_all, fields(actor=%account.uri, object=%self.id.inner()))]
     async fn handle_top_level_note(
         &self,
         data: &Data<crate::FederationData>,
         account: &Account,
-    ) -> Result<(), ActivityError> {
+        origin: CommentOrigin,
+    ) -> Result<Option<Note>, ActivityError> {
         // A top-level note must contain the #fediscus tag, otherwise it's not interesting to us
         if !self.has_tag() {
             debug!("Note does not have #fediscus tag, ignoring");
-            return Ok(());
+            return Ok(None);
         }
 
         let urls = self
@@ -36,21 +195,31 @@ impl APubNote {
         // And it must have at least one link to the blog post, duh!
         if urls.is_empty() {
             debug!("Note does not have any links, ignoring");
-            return Ok(());
+            return Ok(None);
         }
 
         // We are only interested in the first URL, which should point to the blog post. Obviously any
         // of the URLs could point to the blog post, but do we have an oracle to tell us which one?
         let blog_url = &urls[0];
 
-        let blog = data
-            .service
-            .storage()
-            .new_blog(blog_url)
-            .await
-            .map_err(|e| ActivityError::storage(e, "Failed to create new blog"))?;
+        let blog = blog_for_url(data, blog_url).await?;
 
-        data.service
+        // Root notes announce the blog post, so the moderation policy of the blog doesn't apply
+        // to them, but they are still subject to the filters
+        let moderation_state = match data.filters.check(self, account, &blog, origin).await {
+            FilterVerdict::Accept => ModerationState::Approved,
+            FilterVerdict::Hold(reason) => {
+                info!("Holding note for moderation: {}", reason);
+                ModerationState::Pending
+            }
+            FilterVerdict::Reject(reason) => {
+                info!("Rejecting note: {}", reason);
+                return Ok(None);
+            }
+        };
+
+        let note = data
+            .service
             .storage()
             .new_post(
                 account.id,
@@ -58,11 +227,16 @@ impl APubNote {
                 None,
                 None,
                 blog.id,
+                NoteContent::from(self),
+                moderation_state,
             )
             .await
             .map_err(|e| ActivityError::storage(e, "Failed to create new post"))?;
 
-        Ok(())
+        // Import any replies that were written before we learned about the note
+        jobs::spawn_backfill_thread(self.clone(), data);
+
+        Ok(Some(note))
     }
 
     #[instrument(name="create_note_reply", skip_all, fields(actor=%account.uri, object=%self.id.inner()))]
@@ -71,7 +245,34 @@ impl APubNote {
         data: &Data<crate::FederationData>,
         account: &Account,
         parent_note: &Note,
-    ) -> Result<(), ActivityError> {
+        origin: CommentOrigin,
+    ) -> Result<Option<Note>, ActivityError> {
+        let blog = data
+            .service
+            .storage()
+            .blog_by_id(parent_note.blog_id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to look up blog"))?
+            .ok_or_else(|| ActivityError::invalid_data("Reply belongs to an unknown blog"))?;
+
+        let moderation_state = match data.filters.check(self, account, &blog, origin).await {
+            FilterVerdict::Accept => {
+                let state = moderation_state_for_reply(data, account, &blog).await?;
+                if state == ModerationState::Pending {
+                    info!("Holding reply for moderation");
+                }
+                state
+            }
+            FilterVerdict::Hold(reason) => {
+                info!("Holding reply for moderation: {}", reason);
+                ModerationState::Pending
+            }
+            FilterVerdict::Reject(reason) => {
+                info!("Rejecting reply: {}", reason);
+                return Ok(None);
+            }
+        };
+
         data.service
             .storage()
             .new_post(
@@ -80,10 +281,12 @@ impl APubNote {
                 Some(parent_note.id),
                 parent_note.root_id.or(Some(parent_note.id)),
                 parent_note.blog_id,
+                NoteContent::from(self),
+                moderation_state,
             )
             .await
-            .map_err(|e| ActivityError::storage(e, "Failed to create new reply post"))?;
-        Ok(())
+            .map(Some)
+            .map_err(|e| ActivityError::storage(e, "Failed to create new reply post"))
     }
 }
 
@@ -93,38 +296,43 @@ pub struct CreateNote {
     r#type: CreateType,
     actor: ObjectId<storage::Account>,
     id: ObjectId<storage::Note>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    to: Vec<Url>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    cc: Vec<Url>,
     object: APubNote,
 }
 
 impl CreateNote {
-    /// Attempts to find the parent note if this is a reply
-    async fn find_parent_note(
-        &self,
-        data: &Data<<Self as ActivityHandler>::DataType>,
-    ) -> Result<Option<Note>, ActivityError> {
-        match &self.object.in_reply_to {
-            Some(id) => data
-                .app_data()
-                .service
-                .storage()
-                .post_by_uri(&id.inner().clone().into())
-                .await
-                .map_err(|e| ActivityError::storage(e, "Failed to find parent note")),
-            None => Ok(None),
-        }
-    }
-
-    /// Processes the note based on whether it's a reply or top-level note
-    async fn process_note(
-        self,
-        data: &Data<<Self as ActivityHandler>::DataType>,
-        account: &Account,
-        parent_note: Option<Note>,
+    /// Publishes a note of our account to all of its followers
+    #[instrument(skip_all)]
+    pub async fn send(
+        actor: &storage::Account,
+        note: APubNote,
+        data: &Data<crate::FederationData>,
     ) -> Result<(), ActivityError> {
-        match parent_note {
-            Some(parent) => self.object.handle_reply_note(data, account, &parent).await,
-            None => self.object.handle_top_level_note(data, account).await,
-        }
+        let inboxes = data
+            .service
+            .storage()
+            .follower_inboxes(actor.id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find follower inboxes"))?
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let create = WithContext::new_default(CreateNote {
+            r#type: CreateType::Create,
+            actor: actor.uri.clone().into(),
+            id: generate_activity_id(data)?.into(),
+            to: note.to.clone(),
+            cc: note.cc.clone(),
+            object: note,
+        });
+        save_to_outbox(create.inner(), actor, true, data).await?;
+
+        queue_activity(&create, actor, inboxes, data)
+            .await
+            .map_err(|e| ActivityError::federation(e, "Failed to queue CreateNote"))
     }
 }
 
@@ -142,9 +350,23 @@ impl ActivityHandler for CreateNote {
     }
 
     async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        // TODO
-        // 1) verify that the actor is followed by us
-        Ok(())
+        // No follow check here: replies come from anyone who found the discussion, not only from
+        // accounts we follow, and top-level notes are picked up by their #fediscus tag instead.
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.attributed_to.inner(),
+            "Note is not attributed to the actor",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            self.id.inner(),
+            "Create activity is not hosted by the actor",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            self.object.id.inner(),
+            "Note is not hosted by the actor",
+        )
     }
 
     /// Handles the receipt of a new note activity
@@ -153,14 +375,11 @@ impl ActivityHandler for CreateNote {
     /// and replies appropriately.
     async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
         info!("Received note from {}", self.actor.inner());
-        let account = self
-            .object
-            .attributed_to
-            .dereference(data)
-            .await
-            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))?;
+        let account = self.object.author(data).await?;
 
-        let parent_note = self.find_parent_note(data).await?;
-        self.process_note(data, &account, parent_note).await
+        self.object
+            .process_new_note(data, &account, CommentOrigin::Delivered)
+            .await?;
+        Ok(())
     }
 }
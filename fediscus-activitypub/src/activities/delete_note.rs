@@ -11,7 +11,7 @@ use url::Url;
 use crate::apub::DeleteNote;
 use crate::FederationData;
 
-use super::ActivityError;
+use super::{verification, ActivityError};
 
 #[async_trait]
 impl ActivityHandler for DeleteNote {
@@ -26,8 +26,19 @@ impl ActivityHandler for DeleteNote {
         self.actor.inner()
     }
 
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+    /// Verifies that the actor deletes their own note
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Delete activity is not hosted by the actor",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.object.id,
+            "Deleted note is not hosted by the actor",
+        )?;
+        verification::note_owner(&self.object.id, self.actor.inner(), data).await
     }
 
     #[instrument(name = "receive_delete_note", skip_all, fields(actor=%self.actor, object=%self.object.id))]
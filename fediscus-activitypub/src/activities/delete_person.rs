@@ -0,0 +1,49 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::config::Data;
+use activitypub_federation::traits::ActivityHandler;
+use async_trait::async_trait;
+use tracing::{info, instrument};
+use url::Url;
+
+use crate::apub::DeletePerson;
+use crate::FederationData;
+
+use super::{verification, ActivityError};
+
+#[async_trait]
+impl ActivityHandler for DeletePerson {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    /// Verifies that the actor deletes their own account
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            &self.object,
+            "Actor can only delete their own account",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Delete activity is not hosted by the actor",
+        )
+    }
+
+    #[instrument(name = "receive_delete_person", skip_all, fields(actor=%self.actor))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        info!("Received account delete from {}", self.actor);
+
+        data.service.delete_account(self.object.into()).await
+    }
+}
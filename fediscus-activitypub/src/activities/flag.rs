@@ -0,0 +1,79 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::activity_queue::queue_activity;
+use activitypub_federation::config::Data;
+use activitypub_federation::protocol::context::WithContext;
+use activitypub_federation::traits::{ActivityHandler, Actor};
+use async_trait::async_trait;
+use tracing::instrument;
+use url::Url;
+
+use crate::apub::Flag;
+use crate::{storage, FederationData};
+
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
+
+impl Flag {
+    /// Reports the notes of the account to the account's instance
+    #[instrument(skip_all, fields(reported=%reported.uri))]
+    pub async fn send(
+        actor: &storage::Account,
+        reported: &storage::Account,
+        notes: Vec<Url>,
+        content: Option<String>,
+        data: &Data<FederationData>,
+    ) -> Result<(), ActivityError> {
+        let mut object = vec![reported.uri.clone().into()];
+        object.extend(notes);
+        let flag = WithContext::new_default(Flag::new(
+            actor.uri.clone().into(),
+            object,
+            content,
+            generate_activity_id(data)?,
+        ));
+
+        // Reports are only meant for the moderators of the instance
+        save_to_outbox(flag.inner(), actor, false, data).await?;
+
+        queue_activity(&flag, actor, vec![reported.shared_inbox_or_inbox()], data)
+            .await
+            .map_err(|e| ActivityError::federation(e, "Failed to queue flag activity"))
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for Flag {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Flag activity is not hosted by the actor",
+        )?;
+        verification::any_local_object(&self.object, data).await
+    }
+
+    #[instrument(name="flag_receive", skip_all, fields(actor=%self.actor.inner()))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let account = self
+            .actor
+            .dereference(data)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))?;
+        data.service
+            .handle_report(&account, self.id.into(), self.content, self.object)
+            .await
+    }
+}
@@ -14,7 +14,7 @@ use url::Url;
 use crate::apub::Follow;
 use crate::{storage, FederationData};
 
-use super::{generate_activity_id, ActivityError};
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
 
 impl Follow {
     #[instrument(skip_all)]
@@ -34,6 +34,8 @@ impl Follow {
             .await
             .map_err(|e| ActivityError::storage(e, "Failed to save follow activity"))?;
 
+        save_to_outbox(follow.inner(), actor, false, data).await?;
+
         queue_activity(&follow, actor, vec![object.shared_inbox_or_inbox()], data)
             .await
             .map_err(|e| ActivityError::federation(e, "Failed to queue follow activity"))
@@ -54,8 +56,13 @@ impl ActivityHandler for Follow {
     }
 
     /// Verifies that the object that is being followed is a local account.
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::domains_match(
+            self.actor.inner(),
+            self.id.inner(),
+            "Follow activity is not hosted by the actor",
+        )?;
+        verification::local_account(self.object.inner(), data).await
     }
 
     #[instrument(name = "receive_follow", skip_all, fields(actor=%self.actor, object=%self.object))]
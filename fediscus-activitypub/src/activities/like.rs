@@ -7,7 +7,7 @@ use url::Url;
 use crate::apub::Like;
 use crate::FederationData;
 
-use super::ActivityError;
+use super::{verification, ActivityError};
 
 #[async_trait]
 impl ActivityHandler for Like {
@@ -23,13 +23,22 @@ impl ActivityHandler for Like {
     }
 
     async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Like activity is not hosted by the actor",
+        )
     }
 
     #[instrument(name="like_receive", skip_all, fields(actor=%self.actor.inner(), object=%self.object.inner()))]
     async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let account = self
+            .actor
+            .dereference(data)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))?;
         data.service
-            .like_post(self.object.inner().clone().into())
+            .like_post(self.object.inner().clone().into(), &account, self.id.into())
             .await
             .map_err(|e| ActivityError::processing(e, "Failed to process like"))?;
         Ok(())
@@ -4,22 +4,33 @@
 // SPDX-License-Identifier: MIT
 
 use activitypub_federation::config::Data;
+use activitypub_federation::traits::ActivityHandler;
+use serde::Serialize;
 use thiserror::Error;
 use url::Url;
 use uuid::Uuid;
 
+pub(crate) use create_note::blog_for_url;
 pub use create_note::CreateNote;
 
 use crate::{storage, FederationData};
 
 mod accept_follow;
+mod announce;
 mod create_note;
 mod delete_note;
+mod delete_person;
+mod flag;
 mod follow;
 mod like;
+mod move_account;
 mod reject_follow;
+mod undo_announce;
 mod undo_follow;
 mod undo_like;
+mod update_note;
+mod update_person;
+mod verification;
 
 #[derive(Error, Debug)]
 pub enum ActivityError {
@@ -129,3 +140,37 @@ fn generate_activity_id(data: &Data<FederationData>) -> Result<Url, ActivityErro
     ))
     .map_err(|e| ActivityError::invalid_data(format!("Failed to generate activity ID: {}", e)))
 }
+
+/// Generates the ID of a new note of our account, served at `/notes/:id`
+pub(crate) fn generate_note_id(data: &Data<FederationData>) -> Result<Url, ActivityError> {
+    let id = Uuid::new_v4();
+    Url::parse(&format!(
+        "https://{}/notes/{}",
+        data.config.fediverse_user.host, id
+    ))
+    .map_err(|e| ActivityError::invalid_data(format!("Failed to generate note ID: {}", e)))
+}
+
+/// Records an activity sent by our account.
+///
+/// Only `public` activities are listed in the account's outbox, activities addressed to a single
+/// account (follows and their responses, undone likes) would otherwise reveal who we interact
+/// with.
+async fn save_to_outbox<A>(
+    activity: &A,
+    actor: &storage::Account,
+    public: bool,
+    data: &Data<FederationData>,
+) -> Result<(), ActivityError>
+where
+    A: ActivityHandler + Serialize + Sync,
+{
+    let json = serde_json::to_value(activity)
+        .map_err(|e| ActivityError::processing(e, "Failed to serialize activity"))?;
+    data.service
+        .storage()
+        .new_outbox_activity(actor.id, &activity.id().clone().into(), json, public)
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to save activity to outbox"))?;
+    Ok(())
+}
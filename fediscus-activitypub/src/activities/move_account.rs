@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::config::Data;
+use activitypub_federation::fetch::fetch_object_http;
+use activitypub_federation::traits::ActivityHandler;
+use async_trait::async_trait;
+use tracing::{info, instrument};
+use url::Url;
+
+use crate::apub::{self, Move};
+use crate::FederationData;
+
+use super::{verification, ActivityError};
+
+#[async_trait]
+impl ActivityHandler for Move {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    /// Verifies that the actor moves their own account and that the new account confirms
+    /// the move by listing the old one in its `alsoKnownAs`
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.inner(),
+            "Actor can only move their own account",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Move activity is not hosted by the actor",
+        )?;
+
+        // Always fetch the target, a cached copy may predate the alias
+        let target = fetch_object_http::<_, apub::Person>(self.target.inner(), data)
+            .await
+            .map_err(|e| ActivityError::federation(e, "Failed to fetch move target"))?
+            .object;
+        verification::domains_match(
+            self.target.inner(),
+            target.id.inner(),
+            "Move target is not hosted by its instance",
+        )?;
+        verification::also_known_as(&target, self.actor.inner())
+    }
+
+    #[instrument(name = "receive_move", skip_all, fields(actor=%self.actor, target=%self.target))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        info!("Received move of {} to {}", self.actor, self.target);
+
+        let from = self.actor.dereference(data).await.map_err(|e| {
+            ActivityError::storage(e, format!("Failed to dereference actor {}", self.actor))
+        })?;
+        // Refetch the target, a cached copy may predate the alias
+        let to = self.target.dereference_forced(data).await.map_err(|e| {
+            ActivityError::storage(e, format!("Failed to dereference target {}", self.target))
+        })?;
+
+        data.service.move_account(from, to, data).await
+    }
+}
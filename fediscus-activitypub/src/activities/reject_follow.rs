@@ -16,7 +16,7 @@ use url::Url;
 use crate::apub::{Follow, RejectFollow};
 use crate::{storage, FederationData};
 
-use super::{generate_activity_id, ActivityError};
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
 
 #[derive(Error, Debug)]
 pub enum RejectError {
@@ -39,6 +39,8 @@ impl RejectFollow {
             object,
             generate_activity_id(data)?,
         ));
+        save_to_outbox(accept.inner(), actor, false, data).await?;
+
         queue_activity(&accept, actor, vec![inbox], data)
             .await
             .map_err(|e| ActivityError::processing(e, "Failed to send RejectFollow"))
@@ -58,8 +60,19 @@ impl ActivityHandler for RejectFollow {
         self.actor.inner()
     }
 
-    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+    /// Verifies that the follow is ours and that it's rejected by the followed account.
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.object.inner(),
+            "Only the followed account can reject the follow",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Reject activity is not hosted by the actor",
+        )?;
+        verification::local_account(self.object.actor.inner(), data).await
     }
 
     #[instrument(name = "receive_reject_follow", skip_all, fields(actor=%self.actor))]
@@ -14,7 +14,7 @@ use url::Url;
 use crate::apub::{Follow, UndoFollow};
 use crate::{storage, FederationData};
 
-use super::{generate_activity_id, ActivityError};
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
 
 impl UndoFollow {
     #[instrument(skip_all)]
@@ -29,6 +29,8 @@ impl UndoFollow {
             follow,
             generate_activity_id(data)?,
         ));
+        save_to_outbox(activity.inner(), actor, false, data).await?;
+
         queue_activity(&activity, actor, vec![inbox], data)
             .await
             .map_err(|e| ActivityError::processing(e, "Failed to send undo follow"))
@@ -49,7 +51,16 @@ impl ActivityHandler for UndoFollow {
     }
 
     async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.actor.inner(),
+            "Only the follower can undo the follow",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Undo activity is not hosted by the actor",
+        )
     }
 
     #[instrument(name = "receive_undo_follow", skip_all, fields(actor=%self.actor))]
@@ -14,7 +14,7 @@ use url::Url;
 use crate::apub::{Like, UndoLike};
 use crate::{storage, FederationData};
 
-use super::{generate_activity_id, ActivityError};
+use super::{generate_activity_id, save_to_outbox, verification, ActivityError};
 
 impl UndoLike {
     #[instrument(skip_all)]
@@ -29,6 +29,8 @@ impl UndoLike {
             like,
             generate_activity_id(data)?,
         ));
+        save_to_outbox(activity.inner(), actor, false, data).await?;
+
         queue_activity(&activity, actor, vec![inbox], data)
             .await
             .map_err(|e| ActivityError::federation(e, "Failed to queue activity"))
@@ -49,13 +51,27 @@ impl ActivityHandler for UndoLike {
     }
 
     async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
-        Ok(())
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.actor.inner(),
+            "Only the author of the like can undo it",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Undo activity is not hosted by the actor",
+        )
     }
 
     #[instrument(name="undo_like_receive", skip_all, fields(actor=%self.actor.inner(), object=%self.object.object.inner()))]
     async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        let account = self
+            .actor
+            .dereference(data)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to dereference actor"))?;
         data.service
-            .unlike_post(self.object.object.inner().clone().into())
+            .unlike_post(self.object.object.inner().clone().into(), &account)
             .await
             .map_err(|e| ActivityError::processing(e, "Failed to process unlike"))?;
         Ok(())
@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::config::Data;
+use activitypub_federation::traits::ActivityHandler;
+use async_trait::async_trait;
+use tracing::{debug, info, instrument};
+use url::Url;
+
+use crate::apub::UpdateNote;
+use crate::filters::{CommentOrigin, FilterVerdict};
+use crate::storage::{Blog, ModerationState, Note, NoteContent};
+use crate::FederationData;
+
+use super::create_note::blog_for_url;
+use super::{verification, ActivityError};
+
+impl UpdateNote {
+    /// Re-evaluates whether an edited top-level note still belongs to a blog post.
+    ///
+    /// Returns the blog the note now belongs to, or `None` if the note no longer qualifies and
+    /// the whole thread has been removed.
+    async fn update_root_note(
+        &self,
+        data: &Data<FederationData>,
+        note: &Note,
+    ) -> Result<Option<Blog>, ActivityError> {
+        let urls = if self.object.has_tag() {
+            self.object
+                .get_links()
+                .map_err(|_| ActivityError::invalid_data("Note does not have any links"))?
+        } else {
+            Vec::new()
+        };
+
+        let Some(blog_url) = urls.first() else {
+            debug!("Note no longer has #fediscus tag or a link, removing the thread");
+            data.service
+                .storage()
+                .delete_post_by_id(note.id)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to delete note"))?;
+            return Ok(None);
+        };
+
+        let blog = blog_for_url(data, blog_url).await?;
+        if blog.id != note.blog_id {
+            debug!("Note now links to {}, moving the thread", blog_url);
+            data.service
+                .storage()
+                .move_thread_to_blog(note.id, blog.id)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to move thread"))?;
+        }
+
+        Ok(Some(blog))
+    }
+}
+
+#[async_trait]
+impl ActivityHandler for UpdateNote {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    /// Verifies that the actor updates their own note
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.attributed_to.inner(),
+            "Note is not attributed to the actor",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Update activity is not hosted by the actor",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            self.object.id.inner(),
+            "Note is not hosted by the actor",
+        )?;
+        verification::note_owner(self.object.id.inner(), self.actor.inner(), data).await
+    }
+
+    #[instrument(name = "receive_update_note", skip_all, fields(actor=%self.actor.inner(), object=%self.object.id.inner()))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        info!("Received note update from {}", self.actor.inner());
+
+        let storage = data.service.storage();
+        let note = storage
+            .post_by_uri(&self.object.id.inner().clone().into())
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find note"))?;
+
+        let Some(note) = note else {
+            // The note may have only now gained the #fediscus tag or a link to the blog,
+            // so treat it as if it was just created.
+            debug!("Note not known yet, processing it as a new note");
+            let account = self.object.author(data).await?;
+            self.object
+                .process_new_note(data, &account, CommentOrigin::Delivered)
+                .await?;
+            return Ok(());
+        };
+
+        if note.is_deleted() {
+            debug!("Note has been deleted, ignoring the update");
+            return Ok(());
+        }
+
+        let blog = if note.reply_to_id.is_none() {
+            match self.update_root_note(data, &note).await? {
+                Some(blog) => blog,
+                None => return Ok(()),
+            }
+        } else {
+            storage
+                .blog_by_id(note.blog_id)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to look up blog"))?
+                .ok_or_else(|| ActivityError::invalid_data("Note belongs to an unknown blog"))?
+        };
+
+        // The new content could have been rejected or held had the note been created with it
+        let account = self.object.author(data).await?;
+        match data
+            .filters
+            .check(&self.object, &account, &blog, CommentOrigin::Edited)
+            .await
+        {
+            FilterVerdict::Accept => {}
+            FilterVerdict::Hold(reason) => {
+                info!("Holding updated note for moderation: {}", reason);
+                storage
+                    .set_moderation_state(note.id, ModerationState::Pending)
+                    .await
+                    .map_err(|e| ActivityError::storage(e, "Failed to hold note"))?;
+            }
+            FilterVerdict::Reject(reason) => {
+                // Same as when the note is deleted, its replies keep their place in the thread
+                info!("Rejecting updated note: {}", reason);
+                storage
+                    .tombstone_post(note.id)
+                    .await
+                    .map_err(|e| ActivityError::storage(e, "Failed to remove note"))?;
+                return Ok(());
+            }
+        }
+
+        storage
+            .update_post(note.id, NoteContent::from(&self.object))
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to update note"))?;
+        Ok(())
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::config::Data;
+use activitypub_federation::traits::ActivityHandler;
+use async_trait::async_trait;
+use tracing::{info, instrument};
+use url::Url;
+
+use crate::apub::UpdatePerson;
+use crate::FederationData;
+
+use super::{verification, ActivityError};
+
+#[async_trait]
+impl ActivityHandler for UpdatePerson {
+    type DataType = FederationData;
+    type Error = ActivityError;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    /// Verifies that the actor updates their own profile
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verification::urls_match(
+            self.actor.inner(),
+            self.object.id.inner(),
+            "Actor can only update their own profile",
+        )?;
+        verification::domains_match(
+            self.actor.inner(),
+            &self.id,
+            "Update activity is not hosted by the actor",
+        )
+    }
+
+    #[instrument(name = "receive_update_person", skip_all, fields(actor=%self.actor))]
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        info!("Received profile update from {}", self.actor);
+
+        data.service
+            .storage()
+            .update_or_insert_account(&self.object)
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,202 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Checks shared by the `verify` implementations of the activity handlers
+
+use activitypub_federation::config::Data;
+use activitypub_federation::protocol::verification::{verify_domains_match, verify_urls_match};
+use thiserror::Error;
+use url::Url;
+
+use crate::{apub, FederationData};
+
+use super::ActivityError;
+
+#[derive(Error, Debug)]
+pub enum VerificationError {
+    #[error("{0} is not our local account")]
+    NotLocalAccount(Url),
+    #[error("{actor} does not own {object}")]
+    NotOwner { actor: Url, object: Url },
+    #[error("{target} is not also known as {actor}")]
+    NotAlias { actor: Url, target: Url },
+    #[error("None of the objects is hosted by us")]
+    NoLocalObject,
+}
+
+/// Verifies that both URLs are identical
+pub(super) fn urls_match(a: &Url, b: &Url, context: &str) -> Result<(), ActivityError> {
+    verify_urls_match(a, b).map_err(|e| ActivityError::verification(e, context))
+}
+
+/// Verifies that both URLs belong to the same domain
+pub(super) fn domains_match(a: &Url, b: &Url, context: &str) -> Result<(), ActivityError> {
+    verify_domains_match(a, b).map_err(|e| ActivityError::verification(e, context))
+}
+
+/// Verifies that the URL is the ID of our local account
+pub(super) async fn local_account(
+    url: &Url,
+    data: &Data<FederationData>,
+) -> Result<(), ActivityError> {
+    let local = data.service.storage().get_local_account().await?;
+    if local.uri.as_url() != url {
+        return Err(ActivityError::verification(
+            VerificationError::NotLocalAccount(url.clone()),
+            "Activity must target our local account",
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies that the actor is the author of the note, if we know the note
+pub(super) async fn note_owner(
+    note: &Url,
+    actor: &Url,
+    data: &Data<FederationData>,
+) -> Result<(), ActivityError> {
+    let storage = data.service.storage();
+    let Some(post) = storage
+        .post_by_uri(&note.clone().into())
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to find note"))?
+    else {
+        return Ok(());
+    };
+
+    let is_owner = storage
+        .account_by_id(post.account_id)
+        .await?
+        .is_some_and(|owner| owner.uri.as_url() == actor);
+    if !is_owner {
+        return Err(ActivityError::verification(
+            VerificationError::NotOwner {
+                actor: actor.clone(),
+                object: note.clone(),
+            },
+            "Actor does not own the note",
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies that at least one of the objects is hosted on the domain of our local account
+pub(super) async fn any_local_object(
+    objects: &[Url],
+    data: &Data<FederationData>,
+) -> Result<(), ActivityError> {
+    let local = data.service.storage().get_local_account().await?;
+    if !objects
+        .iter()
+        .any(|object| verify_domains_match(object, local.uri.as_url()).is_ok())
+    {
+        return Err(ActivityError::verification(
+            VerificationError::NoLocalObject,
+            "Activity does not concern any of our objects",
+        ));
+    }
+    Ok(())
+}
+
+/// Verifies that the target account of a move lists the actor among its aliases
+pub(super) fn also_known_as(target: &apub::Person, actor: &Url) -> Result<(), ActivityError> {
+    if !target.also_known_as.contains(actor) {
+        return Err(ActivityError::verification(
+            VerificationError::NotAlias {
+                actor: actor.clone(),
+                target: target.id.inner().clone(),
+            },
+            "Move target does not confirm the move",
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AccountStorage, BlogStorage, ModerationState, NoteContent, NoteStorage};
+    use crate::testing::{create_person, federation_config, MemoryStorage};
+
+    #[tokio::test]
+    async fn test_local_account() {
+        let federation = federation_config(MemoryStorage::new("example.com")).await;
+        let data = federation.to_request_data();
+
+        let local = Url::parse("http://example.com/users/fediscus").unwrap();
+        local_account(&local, &data).await.unwrap();
+
+        let other = Url::parse("http://example.com/users/someone").unwrap();
+        let result = local_account(&other, &data).await;
+        assert!(matches!(result, Err(ActivityError::Verification { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_note_owner() {
+        let storage = MemoryStorage::new("example.com");
+        let author = storage
+            .new_account(&create_person("author", "example.com"))
+            .await
+            .unwrap();
+        let blog = storage
+            .new_blog(&Url::parse("https://example.com/blog").unwrap())
+            .await
+            .unwrap();
+        let note_uri = Url::parse("https://example.com/note/1").unwrap();
+        storage
+            .new_post(
+                author.id,
+                note_uri.clone().into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
+            .await
+            .unwrap();
+        let federation = federation_config(storage).await;
+        let data = federation.to_request_data();
+
+        note_owner(&note_uri, author.uri.as_url(), &data)
+            .await
+            .unwrap();
+
+        let intruder = create_person("intruder", "example.com");
+        let result = note_owner(&note_uri, intruder.id.inner(), &data).await;
+        assert!(matches!(result, Err(ActivityError::Verification { .. })));
+
+        // Unknown notes can't be checked, so they pass
+        let unknown = Url::parse("https://example.com/note/2").unwrap();
+        note_owner(&unknown, intruder.id.inner(), &data)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_any_local_object() {
+        let federation = federation_config(MemoryStorage::new("example.com")).await;
+        let data = federation.to_request_data();
+
+        let remote = Url::parse("https://remote.example/notes/1").unwrap();
+        let local = Url::parse("http://example.com/notes/1").unwrap();
+        any_local_object(&[remote.clone(), local], &data)
+            .await
+            .unwrap();
+
+        let result = any_local_object(&[remote], &data).await;
+        assert!(matches!(result, Err(ActivityError::Verification { .. })));
+    }
+
+    #[test]
+    fn test_also_known_as() {
+        let old = create_person("old", "old.example.com");
+        let mut new = create_person("new", "new.example.com");
+        let result = also_known_as(&new, old.id.inner());
+        assert!(matches!(result, Err(ActivityError::Verification { .. })));
+
+        new.also_known_as.push(old.id.inner().clone());
+        also_known_as(&new, old.id.inner()).unwrap();
+    }
+}
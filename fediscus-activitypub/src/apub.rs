@@ -4,23 +4,39 @@
 // SPDX-License-Identifier: MIT
 
 mod accept_follow;
+mod announce;
+mod collection;
 mod delete_note;
+mod delete_person;
+mod flag;
 mod follow;
 mod like;
+mod move_account;
 mod note;
 mod person;
 mod reject_follow;
 mod tombstone;
+mod undo_announce;
 mod undo_follow;
 mod undo_like;
+mod update_note;
+mod update_person;
 
 pub use accept_follow::AcceptFollow;
+pub use announce::Announce;
+pub use collection::{Collection, CollectionPage, ObjectOrLink};
 pub use delete_note::DeleteNote;
+pub use delete_person::DeletePerson;
+pub use flag::Flag;
 pub use follow::Follow;
 pub use like::Like;
+pub use move_account::Move;
 pub use note::Note;
-pub use person::Person;
+pub use person::{ActorType, Endpoints, Image, Person};
 pub use reject_follow::RejectFollow;
 pub use tombstone::Tombstone;
+pub use undo_announce::UndoAnnounce;
 pub use undo_follow::UndoFollow;
 pub use undo_like::UndoLike;
+pub use update_note::UpdateNote;
+pub use update_person::UpdatePerson;
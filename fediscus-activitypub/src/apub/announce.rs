@@ -0,0 +1,18 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{fetch::object_id::ObjectId, kinds::activity::AnnounceType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::storage;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Announce {
+    pub actor: ObjectId<storage::Account>,
+    pub object: ObjectId<storage::Note>,
+    r#type: AnnounceType,
+    pub id: Url,
+}
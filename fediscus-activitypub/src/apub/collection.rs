@@ -0,0 +1,35 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::apub;
+
+/// A property that can either be a link to an object or the object itself
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ObjectOrLink<T> {
+    Link(Url),
+    Object(T),
+}
+
+/// A (possibly ordered) collection of notes, like the `replies` collection of a note
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Collection {
+    pub id: Option<Url>,
+    pub first: Option<ObjectOrLink<CollectionPage>>,
+    #[serde(default, alias = "orderedItems")]
+    pub items: Vec<ObjectOrLink<apub::Note>>,
+}
+
+/// A single page of a collection
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CollectionPage {
+    pub next: Option<Url>,
+    #[serde(default, alias = "orderedItems")]
+    pub items: Vec<ObjectOrLink<apub::Note>>,
+}
@@ -0,0 +1,30 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{fetch::object_id::ObjectId, kinds::activity::DeleteType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::storage;
+
+/// Sent by an instance when an account has been deleted, the object is the account itself
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeletePerson {
+    pub actor: ObjectId<storage::Account>,
+    pub object: Url,
+    r#type: DeleteType,
+    pub id: Url,
+}
+
+impl DeletePerson {
+    pub fn new(actor: ObjectId<storage::Account>, id: Url) -> Self {
+        Self {
+            object: actor.inner().clone(),
+            actor,
+            r#type: DeleteType::Delete,
+            id,
+        }
+    }
+}
@@ -0,0 +1,45 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{
+    fetch::object_id::ObjectId, kinds::activity::FlagType,
+    protocol::helpers::deserialize_one_or_many,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::storage;
+
+/// A report of abusive content, sent to the instance hosting it
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+    /// Who sent the report
+    pub actor: ObjectId<storage::Account>,
+    /// The reported account, followed by the reported notes
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub object: Vec<Url>,
+    /// Comment of the reporter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    r#type: FlagType,
+    pub id: Url,
+}
+
+impl Flag {
+    pub fn new(
+        actor: ObjectId<storage::Account>,
+        object: Vec<Url>,
+        content: Option<String>,
+        id: Url,
+    ) -> Self {
+        Self {
+            actor,
+            object,
+            content,
+            r#type: FlagType::Flag,
+            id,
+        }
+    }
+}
@@ -0,0 +1,37 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{fetch::object_id::ObjectId, kinds::activity::MoveType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::storage;
+
+/// Sent by an account that has migrated to another account, the object is the old account
+/// and the target is the new one
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Move {
+    pub actor: ObjectId<storage::Account>,
+    pub object: ObjectId<storage::Account>,
+    pub target: ObjectId<storage::Account>,
+    r#type: MoveType,
+    pub id: Url,
+}
+
+impl Move {
+    pub fn new(
+        actor: ObjectId<storage::Account>,
+        target: ObjectId<storage::Account>,
+        id: Url,
+    ) -> Self {
+        Self {
+            object: actor.clone(),
+            actor,
+            target,
+            r#type: MoveType::Move,
+            id,
+        }
+    }
+}
@@ -3,22 +3,28 @@
 //
 // SPDX-License-Identifier: MIT
 
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
-use activitypub_federation::{fetch::object_id::ObjectId, kinds::object::NoteType};
+use activitypub_federation::{
+    fetch::object_id::ObjectId,
+    kinds::{object::NoteType, public},
+    protocol::helpers::deserialize_one_or_many,
+};
 use chrono::{DateTime, Utc};
 use html_parser::Dom;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 use url::Url;
 
-use crate::storage;
+use crate::{apub, storage};
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Tag {
     pub r#type: String,
-    pub href: Url,
+    // Emoji tags don't have a href
+    pub href: Option<Url>,
     pub name: String,
 }
 
@@ -31,20 +37,87 @@ pub struct Note {
     pub published: Option<DateTime<Utc>>,
     pub url: Option<Url>,
     pub attributed_to: ObjectId<storage::Account>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub to: Vec<Url>,
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub cc: Vec<Url>,
+    /// Content warning, if any
+    pub summary: Option<String>,
     pub content: String,
+    /// The content keyed by language code, Mastodon uses it to convey the language of the note
+    pub content_map: Option<HashMap<String, String>>,
     #[serde(default)]
     pub tag: Vec<Tag>,
+    /// Collection of replies to this note
+    pub replies: Option<apub::ObjectOrLink<apub::Collection>>,
 }
 
 impl Note {
+    /// Creates a public note of our account that announces a new blog post
+    pub fn announcement(id: Url, author: &storage::Account, url: &Url, title: &str) -> Self {
+        let content = format!(
+            r#"<p><a href="{url}">{title}</a></p><p>#fediscus</p>"#,
+            url = escape_html(url.as_str()),
+            title = escape_html(title)
+        );
+        Self {
+            r#type: NoteType::Note,
+            id: id.into(),
+            in_reply_to: None,
+            published: Some(Utc::now()),
+            url: None,
+            attributed_to: author.uri.clone().into(),
+            to: vec![public()],
+            cc: author.followers_url().into_iter().collect(),
+            summary: None,
+            content,
+            content_map: None,
+            tag: vec![Tag {
+                r#type: "Hashtag".to_string(),
+                href: None,
+                name: "#fediscus".to_string(),
+            }],
+            replies: None,
+        }
+    }
+
     pub fn has_tag(&self) -> bool {
         self.tag.iter().any(|tag| {
             tag.r#type.eq_ignore_ascii_case("hashtag") && tag.name.eq_ignore_ascii_case("#fediscus")
         }) || self.content.contains("#fediscus")
     }
 
+    /// Returns the language of the note, if the author's instance provided one.
+    pub fn language(&self) -> Option<String> {
+        self.content_map
+            .as_ref()
+            .and_then(|map| map.keys().next().cloned())
+    }
+
     /// Retrieves all the hyperlinks (URLs) from the HTML content.
     pub fn get_links(&self) -> Result<Vec<Url>, html_parser::Error> {
+        let mut links = self.anchor_links(true)?;
+
+        // Fallback to simply looking for an HTTP(s) URL in the content
+        static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://[^\s]+").unwrap());
+        RE.find_iter(&self.content)
+            .filter_map(|m| Url::parse(m.as_str()).ok())
+            .for_each(|url| links.push(url));
+
+        Ok(links)
+    }
+
+    /// Retrieves the distinct links the author has put into the HTML content, that is without
+    /// the links of hashtags and mentions.
+    pub fn content_links(&self) -> Result<Vec<Url>, html_parser::Error> {
+        let mut links = self.anchor_links(false)?;
+        links.sort();
+        links.dedup();
+        Ok(links)
+    }
+
+    /// Retrieves the targets of the `<a>` elements in the HTML content, except for hashtags
+    fn anchor_links(&self, include_mentions: bool) -> Result<Vec<Url>, html_parser::Error> {
         let dom = Dom::parse(&self.content)?;
         let mut links = Vec::new();
         let mut stack = Vec::new();
@@ -63,6 +136,9 @@ impl Note {
                                 continue;
                             }
                         }
+                        if !include_mentions && element.classes.iter().any(|c| c == "mention") {
+                            continue;
+                        }
 
                         if let Ok(url) = Url::parse(href) {
                             links.push(url);
@@ -75,12 +151,13 @@ impl Note {
             }
         }
 
-        // Fallback to simply looking for an HTTP(s) URL in the content
-        static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"https?://[^\s]+").unwrap());
-        RE.find_iter(&self.content)
-            .filter_map(|m| Url::parse(m.as_str()).ok())
-            .for_each(|url| links.push(url));
-
         Ok(links)
     }
 }
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -4,22 +4,81 @@
 // SPDX-License-Identifier: MIT
 
 use activitypub_federation::{
-    fetch::object_id::ObjectId, kinds::actor::PersonType, protocol::public_key::PublicKey,
+    fetch::object_id::ObjectId,
+    kinds::object::ImageType,
+    protocol::{helpers::deserialize_one_or_many, public_key::PublicKey},
 };
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use url::Url;
 
 use crate::storage;
 
+/// The kinds of actors we understand, automated accounts are usually marked as `Service`
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq, Eq)]
+pub enum ActorType {
+    Person,
+    Service,
+    Application,
+}
+
+/// An image attached to an actor, such as their avatar
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Image {
+    #[serde(rename = "type", default)]
+    pub kind: ImageType,
+    pub url: Url,
+}
+
+impl Image {
+    pub fn new(url: Url) -> Self {
+        Self {
+            kind: ImageType::Image,
+            url,
+        }
+    }
+}
+
+/// Endpoints the actor shares with other actors on the same instance
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    pub shared_inbox: Option<Url>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct Person {
     #[serde(rename = "type")]
-    pub kind: PersonType,
+    pub kind: ActorType,
     pub preferred_username: String,
+    /// The display name
+    pub name: Option<String>,
+    /// The avatar
+    pub icon: Option<Image>,
+    /// Link to the profile page
+    pub url: Option<Url>,
+    /// Other accounts of the same person, required to move from one of them to this one
+    #[serde(default, deserialize_with = "deserialize_one_or_many")]
+    pub also_known_as: Vec<Url>,
+    /// When the account was created
+    pub published: Option<DateTime<Utc>>,
     pub id: ObjectId<storage::Account>,
     pub inbox: Url,
     pub outbox: Option<Url>,
-    pub shared_inbox: Option<Url>,
+    pub endpoints: Option<Endpoints>,
+    pub followers: Option<Url>,
+    pub following: Option<Url>,
     pub public_key: PublicKey,
 }
+
+impl Person {
+    pub fn is_bot(&self) -> bool {
+        self.kind != ActorType::Person
+    }
+
+    pub fn shared_inbox(&self) -> Option<&Url> {
+        self.endpoints.as_ref()?.shared_inbox.as_ref()
+    }
+}
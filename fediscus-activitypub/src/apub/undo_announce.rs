@@ -0,0 +1,18 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{fetch::object_id::ObjectId, kinds::activity::UndoType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{apub, storage};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UndoAnnounce {
+    pub actor: ObjectId<storage::Account>,
+    pub object: apub::Announce,
+    r#type: UndoType,
+    pub id: Url,
+}
@@ -0,0 +1,29 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use activitypub_federation::{fetch::object_id::ObjectId, kinds::activity::UpdateType};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::{apub, storage};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateNote {
+    pub actor: ObjectId<storage::Account>,
+    pub object: apub::Note,
+    r#type: UpdateType,
+    pub id: Url,
+}
+
+impl UpdateNote {
+    pub fn new(actor: ObjectId<storage::Account>, object: apub::Note, id: Url) -> Self {
+        Self {
+            actor,
+            object,
+            r#type: UpdateType::Update,
+            id,
+        }
+    }
+}
@@ -23,6 +23,12 @@ pub enum ConfigError {
     InvalidDatabaseUrl(String),
     #[error("Pool size must be greater than 0")]
     InvalidPoolSize,
+    #[error("Actor refresh interval must be greater than 0")]
+    InvalidActorRefreshInterval,
+    #[error("Job poll interval must be greater than 0")]
+    InvalidJobPollInterval,
+    #[error("Invalid comment filter pattern: {0}")]
+    InvalidFilterPattern(#[from] regex::Error),
 }
 
 const fn default_false() -> bool {
@@ -33,6 +39,22 @@ const fn default_pool_size() -> u32 {
     10
 }
 
+const fn default_max_ancestor_depth() -> usize {
+    10
+}
+
+const fn default_actor_max_age() -> u64 {
+    24 * 60 * 60
+}
+
+const fn default_actor_refresh_interval() -> u64 {
+    60 * 60
+}
+
+const fn default_job_poll_interval() -> u64 {
+    10
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 /// The HTTP client configuration
@@ -77,6 +99,124 @@ impl Database {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Settings controlling how we interact with other servers
+pub struct Federation {
+    /// How many unknown ancestors of a reply to fetch when looking for the root of its thread
+    /// Defaults to 10 if not specified
+    #[serde(default = "default_max_ancestor_depth")]
+    pub max_ancestor_depth: usize,
+
+    /// Remote actors that have not been updated for this many seconds are re-fetched
+    /// Defaults to one day if not specified
+    #[serde(default = "default_actor_max_age")]
+    pub actor_max_age: u64,
+
+    /// How often (in seconds) to look for remote actors that need to be re-fetched
+    /// Defaults to one hour if not specified
+    #[serde(default = "default_actor_refresh_interval")]
+    pub actor_refresh_interval: u64,
+
+    /// How often (in seconds) to look for jobs requested through the admin API
+    /// Defaults to 10 seconds if not specified
+    #[serde(default = "default_job_poll_interval")]
+    pub job_poll_interval: u64,
+}
+
+impl Federation {
+    /// Validates the federation settings
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.actor_refresh_interval == 0 {
+            return Err(ConfigError::InvalidActorRefreshInterval);
+        }
+        if self.job_poll_interval == 0 {
+            return Err(ConfigError::InvalidJobPollInterval);
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for Federation {
+    fn default() -> Self {
+        Self {
+            max_ancestor_depth: default_max_ancestor_depth(),
+            actor_max_age: default_actor_max_age(),
+            actor_refresh_interval: default_actor_refresh_interval(),
+            job_poll_interval: default_job_poll_interval(),
+        }
+    }
+}
+
+/// What happens to a comment caught by a filter
+#[derive(Clone, Copy, Debug, Default, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum FilterAction {
+    /// The comment is held for moderation (default)
+    #[default]
+    Hold,
+    /// The comment is dropped
+    Reject,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Catches comments containing any of the words or matching any of the patterns
+pub struct KeywordList {
+    /// Words or phrases, matched case-insensitively anywhere in the comment
+    #[serde(default)]
+    pub words: Vec<String>,
+    /// Regular expressions matched against the HTML content of the comment
+    #[serde(default)]
+    pub patterns: Vec<String>,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Catches comments with too many links, mentions and hashtags are not counted
+pub struct LinkLimit {
+    /// The maximum number of distinct links in a comment
+    pub max_links: usize,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Catches comments of accounts that have been created only recently
+pub struct AccountAge {
+    /// How old (in seconds) an account must be for its comments to pass
+    /// Accounts whose instance doesn't tell when they were created always pass
+    pub min_age: u64,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// Catches comments of accounts that comment too often
+pub struct RateLimit {
+    /// How many comments of a single account pass within the period
+    pub max_comments: usize,
+    /// Length of the period in seconds
+    pub period: u64,
+    #[serde(default)]
+    pub action: FilterAction,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// The filters new comments go through before they are stored, all are disabled by default
+pub struct Filters {
+    pub keywords: Option<KeywordList>,
+    pub links: Option<LinkLimit>,
+    pub new_accounts: Option<AccountAge>,
+    pub rate_limit: Option<RateLimit>,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 /// The main service user exposed as federated user
@@ -91,6 +231,10 @@ pub struct FediverseUser {
     pub display_name: String,
     /// The RSA private key of the user in PEM format, the public key will be derived automatically
     pub private_key: String,
+    /// Whether to hide who follows the user and whom the user follows, only the totals are
+    /// published if enabled (default: false)
+    #[serde(default = "default_false")]
+    pub hide_follows: bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -105,6 +249,12 @@ pub struct Config {
     pub database: Database,
     /// The main service user exposed as federated user
     pub fediverse_user: FediverseUser,
+    /// The federation settings
+    #[serde(default)]
+    pub federation: Federation,
+    /// The filters of incoming comments
+    #[serde(default)]
+    pub filters: Filters,
 }
 
 impl Config {
@@ -125,6 +275,9 @@ impl Config {
         cfg.database
             .validate()
             .map_err(|e| config::ConfigError::Message(e.to_string()))?;
+        cfg.federation
+            .validate()
+            .map_err(|e| config::ConfigError::Message(e.to_string()))?;
 
         Ok(cfg)
     }
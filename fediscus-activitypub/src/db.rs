@@ -22,44 +22,6 @@ pub enum DbError {
     Url(url::ParseError),
 }
 
-/*
-pub async fn get_counts(
-    pool: &sqlx::SqlitePool,
-    posts: &Vec<Post>,
-) -> Result<(HashMap<Url, usize>, Vec<Post>), DbError> {
-    let urls: Vec<_> = posts.iter().map(|post| post.url.to_string()).collect();
-
-    let params = format!("?{}", ", ?".repeat(urls.len() - 1));
-    let query_string = format!(
-        "SELECT url, comment_count FROM posts WHERE url IN ({})",
-        params
-    );
-    let mut query = sqlx::query(&query_string);
-    for url in urls {
-        query = query.bind(url);
-    }
-    let counts = query
-        .fetch_all(pool)
-        .await
-        .map_err(DbError::Sqlx)?
-        .iter()
-        .map(|row| -> Result<(Url, usize), DbError> {
-            let url: String = row.get(0);
-            let count: i64 = row.get(1);
-            Ok((Url::parse(&url).map_err(DbError::Url)?, count as usize))
-        })
-        .collect::<Result<HashMap<_, _>, _>>()?;
-
-    let misses = posts
-        .iter()
-        .filter(|post| !counts.contains_key(&post.url))
-        .cloned()
-        .collect();
-
-    Ok((counts, misses))
-}iid
-*/
-
 fn derive_public_key_pem(private_key: &str) -> Result<String, anyhow::Error> {
     RsaPrivateKey::from_pkcs8_pem(private_key)?
         .to_public_key()
@@ -104,8 +66,8 @@ pub async fn init_local_user(
         let public_key = derive_public_key_pem(&config.private_key)?;
         sqlx::query!(
             "INSERT INTO accounts \
-             (uri, username, host, inbox, outbox, private_key, public_key, local) \
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+             (uri, username, host, inbox, outbox, private_key, public_key, local, display_name) \
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
             uri,
             config.username,
             config.host,
@@ -114,6 +76,7 @@ pub async fn init_local_user(
             config.private_key,
             public_key,
             true,
+            config.display_name,
         )
         .execute(pool)
         .await
@@ -0,0 +1,336 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Filters that incoming comments go through before they are stored
+//!
+//! Each filter decides whether a comment is accepted, held for moderation or rejected.
+//! The filters are chained, a comment is rejected as soon as any filter rejects it and
+//! held if any filter wants it held.
+
+mod keywords;
+mod links;
+mod new_accounts;
+mod rate_limit;
+
+use async_trait::async_trait;
+use tracing::debug;
+
+use crate::apub;
+use crate::config::{ConfigError, FilterAction, Filters};
+use crate::storage::{Account, Blog};
+
+pub use keywords::KeywordFilter;
+pub use links::LinkFilter;
+pub use new_accounts::NewAccountFilter;
+pub use rate_limit::RateLimitFilter;
+
+/// The decision of a filter about a comment
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterVerdict {
+    Accept,
+    /// The comment is held for moderation, for the given reason
+    Hold(String),
+    /// The comment is dropped, for the given reason
+    Reject(String),
+}
+
+impl FilterVerdict {
+    /// The verdict about a comment caught by a filter configured with the action
+    fn caught(action: FilterAction, reason: String) -> Self {
+        match action {
+            FilterAction::Hold => FilterVerdict::Hold(reason),
+            FilterAction::Reject => FilterVerdict::Reject(reason),
+        }
+    }
+}
+
+/// How a comment reached us
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentOrigin {
+    /// The comment was delivered to our inbox
+    Delivered,
+    /// The comment was fetched by us, by a backfill or as an ancestor of a delivered reply
+    Imported,
+    /// A new version of a comment we already have was delivered to our inbox
+    Edited,
+}
+
+#[async_trait]
+pub trait CommentFilter: Send + Sync {
+    /// Name of the filter, used in the logs
+    fn name(&self) -> &'static str;
+
+    /// Whether the filter checks comments that reached us the given way, all of them by default
+    fn applies_to(&self, _origin: CommentOrigin) -> bool {
+        true
+    }
+
+    /// Decides about a new comment by the author in a thread about the blog post
+    async fn check(&self, note: &apub::Note, author: &Account, blog: &Blog) -> FilterVerdict;
+}
+
+/// The filters that are applied to every new comment
+#[derive(Default)]
+pub struct FilterChain {
+    filters: Vec<Box<dyn CommentFilter>>,
+}
+
+impl FilterChain {
+    /// Sets up the built-in filters enabled in the configuration
+    pub fn from_config(config: &Filters) -> Result<Self, ConfigError> {
+        let mut chain = Self::default();
+        if let Some(keywords) = &config.keywords {
+            chain.add(Box::new(KeywordFilter::new(keywords)?));
+        }
+        if let Some(links) = &config.links {
+            chain.add(Box::new(LinkFilter::new(links)));
+        }
+        if let Some(new_accounts) = &config.new_accounts {
+            chain.add(Box::new(NewAccountFilter::new(new_accounts)));
+        }
+        if let Some(rate_limit) = &config.rate_limit {
+            chain.add(Box::new(RateLimitFilter::new(rate_limit)));
+        }
+        Ok(chain)
+    }
+
+    /// Appends the filter to the end of the chain
+    pub fn add(&mut self, filter: Box<dyn CommentFilter>) {
+        self.filters.push(filter);
+    }
+
+    /// Runs the comment through the filters, the first rejection wins over any holds
+    pub async fn check(
+        &self,
+        note: &apub::Note,
+        author: &Account,
+        blog: &Blog,
+        origin: CommentOrigin,
+    ) -> FilterVerdict {
+        let mut verdict = FilterVerdict::Accept;
+        for filter in &self.filters {
+            if !filter.applies_to(origin) {
+                continue;
+            }
+            match filter.check(note, author, blog).await {
+                FilterVerdict::Accept => {}
+                FilterVerdict::Hold(reason) => {
+                    debug!("Filter {} holds the comment: {}", filter.name(), reason);
+                    if verdict == FilterVerdict::Accept {
+                        verdict = FilterVerdict::Hold(reason);
+                    }
+                }
+                FilterVerdict::Reject(reason) => {
+                    debug!("Filter {} rejects the comment: {}", filter.name(), reason);
+                    return FilterVerdict::Reject(reason);
+                }
+            }
+        }
+        verdict
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Utc};
+    use url::Url;
+
+    use super::*;
+    use crate::config::{AccountAge, KeywordList, LinkLimit, RateLimit};
+    use crate::storage::{AccountStorage, BlogStorage};
+    use crate::testing::{create_person, MemoryStorage};
+
+    fn note(content: &str) -> apub::Note {
+        serde_json::from_value(serde_json::json!({
+            "type": "Note",
+            "id": "https://remote.example/notes/1",
+            "attributedTo": "https://remote.example/users/author",
+            "content": content,
+        }))
+        .unwrap()
+    }
+
+    async fn author_and_blog() -> (Account, Blog) {
+        let storage = MemoryStorage::new("example.com");
+        let author = storage
+            .new_account(&create_person("author", "remote.example"))
+            .await
+            .unwrap();
+        let blog = storage
+            .new_blog(&Url::parse("https://example.com/blog").unwrap())
+            .await
+            .unwrap();
+        (author, blog)
+    }
+
+    #[tokio::test]
+    async fn test_keyword_filter() {
+        let (author, blog) = author_and_blog().await;
+        let filter = KeywordFilter::new(&KeywordList {
+            words: vec!["Cheap Watches".to_string()],
+            patterns: vec![r"casino\d+".to_string()],
+            action: FilterAction::Reject,
+        })
+        .unwrap();
+
+        let verdict = filter
+            .check(&note("<p>Buy cheap watches!</p>"), &author, &blog)
+            .await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+        let verdict = filter
+            .check(&note("<p>Visit casino24</p>"), &author, &blog)
+            .await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+        let verdict = filter
+            .check(&note("<p>Nice post!</p>"), &author, &blog)
+            .await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+
+        let invalid = KeywordFilter::new(&KeywordList {
+            words: Vec::new(),
+            patterns: vec!["(".to_string()],
+            action: FilterAction::Hold,
+        });
+        assert!(invalid.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_link_filter() {
+        let (author, blog) = author_and_blog().await;
+        let filter = LinkFilter::new(&LinkLimit {
+            max_links: 1,
+            action: FilterAction::Hold,
+        });
+
+        // Mentions and hashtags are not counted, nor are repeated links
+        let content = r#"<p><a href="https://remote.example/@someone" class="u-url mention">@someone</a>
+            <a href="https://example.com/tags/fediscus" rel="tag">#fediscus</a>
+            <a href="https://spam.example/">one</a> <a href="https://spam.example/">again</a></p>"#;
+        let verdict = filter.check(&note(content), &author, &blog).await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+
+        let content = r#"<p><a href="https://spam.example/1">one</a>
+            <a href="https://spam.example/2">two</a></p>"#;
+        let verdict = filter.check(&note(content), &author, &blog).await;
+        assert!(matches!(verdict, FilterVerdict::Hold(_)));
+    }
+
+    #[tokio::test]
+    async fn test_new_account_filter() {
+        let (mut author, blog) = author_and_blog().await;
+        let filter = NewAccountFilter::new(&AccountAge {
+            min_age: 24 * 60 * 60,
+            action: FilterAction::Hold,
+        });
+        let comment = note("<p>Hello</p>");
+
+        // Accounts of unknown age pass
+        author.published = None;
+        let verdict = filter.check(&comment, &author, &blog).await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+
+        author.published = Some((Utc::now() - Duration::hours(1)).naive_utc());
+        let verdict = filter.check(&comment, &author, &blog).await;
+        assert!(matches!(verdict, FilterVerdict::Hold(_)));
+
+        author.published = Some((Utc::now() - Duration::days(30)).naive_utc());
+        let verdict = filter.check(&comment, &author, &blog).await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+    }
+
+    #[tokio::test]
+    async fn test_rate_limit_filter() {
+        let (author, blog) = author_and_blog().await;
+        let filter = RateLimitFilter::new(&RateLimit {
+            max_comments: 2,
+            period: 60 * 60,
+            action: FilterAction::Reject,
+        });
+        let comment = note("<p>Hello</p>");
+
+        for _ in 0..2 {
+            let verdict = filter.check(&comment, &author, &blog).await;
+            assert_eq!(verdict, FilterVerdict::Accept);
+        }
+        let verdict = filter.check(&comment, &author, &blog).await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+
+        // The claimed publication time doesn't get a comment around the limit
+        let mut old = note("<p>Hello</p>");
+        old.published = Some(Utc::now() - Duration::hours(2));
+        let verdict = filter.check(&old, &author, &blog).await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+    }
+
+    #[tokio::test]
+    async fn test_filter_chain() {
+        let (author, blog) = author_and_blog().await;
+        let chain = FilterChain::from_config(&Filters {
+            keywords: Some(KeywordList {
+                words: vec!["casino".to_string()],
+                patterns: Vec::new(),
+                action: FilterAction::Reject,
+            }),
+            links: Some(LinkLimit {
+                max_links: 0,
+                action: FilterAction::Hold,
+            }),
+            new_accounts: None,
+            rate_limit: Some(RateLimit {
+                max_comments: 2,
+                period: 60 * 60,
+                action: FilterAction::Reject,
+            }),
+        })
+        .unwrap();
+        let delivered = CommentOrigin::Delivered;
+
+        let verdict = chain
+            .check(&note("<p>Hello</p>"), &author, &blog, delivered)
+            .await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+
+        let linked = r#"<p><a href="https://example.com/other-post">Related</a></p>"#;
+        let verdict = chain.check(&note(linked), &author, &blog, delivered).await;
+        assert!(matches!(verdict, FilterVerdict::Hold(_)));
+
+        // A rejection wins over a hold of an earlier filter
+        let spam = r#"<p><a href="https://casino.example/">casino</a></p>"#;
+        let verdict = chain.check(&note(spam), &author, &blog, delivered).await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+
+        // The rate limit has been reached, but imported comments are not subject to it
+        let verdict = chain
+            .check(&note("<p>Hello</p>"), &author, &blog, delivered)
+            .await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+        let verdict = chain
+            .check(
+                &note("<p>Hello</p>"),
+                &author,
+                &blog,
+                CommentOrigin::Imported,
+            )
+            .await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+        // Nor are edits of comments we already have
+        let verdict = chain
+            .check(&note("<p>Hello</p>"), &author, &blog, CommentOrigin::Edited)
+            .await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+        // Other filters still apply to them
+        let verdict = chain
+            .check(&note(spam), &author, &blog, CommentOrigin::Imported)
+            .await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+        let verdict = chain
+            .check(&note(spam), &author, &blog, CommentOrigin::Edited)
+            .await;
+        assert!(matches!(verdict, FilterVerdict::Reject(_)));
+
+        let empty = FilterChain::from_config(&Filters::default()).unwrap();
+        let verdict = empty.check(&note(spam), &author, &blog, delivered).await;
+        assert_eq!(verdict, FilterVerdict::Accept);
+    }
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use regex::Regex;
+
+use crate::apub;
+use crate::config::{FilterAction, KeywordList};
+use crate::storage::{Account, Blog};
+
+use super::{CommentFilter, FilterVerdict};
+
+/// Catches comments containing any of the configured words or matching any of the patterns
+pub struct KeywordFilter {
+    /// The configured word or pattern, together with the expression that finds it
+    patterns: Vec<(String, Regex)>,
+    action: FilterAction,
+}
+
+impl KeywordFilter {
+    pub fn new(config: &KeywordList) -> Result<Self, regex::Error> {
+        let words = config.words.iter().map(|word| {
+            Regex::new(&format!("(?i){}", regex::escape(word))).map(|regex| (word.clone(), regex))
+        });
+        let patterns = config
+            .patterns
+            .iter()
+            .map(|pattern| Regex::new(pattern).map(|regex| (pattern.clone(), regex)));
+        Ok(Self {
+            patterns: words.chain(patterns).collect::<Result<_, _>>()?,
+            action: config.action,
+        })
+    }
+}
+
+#[async_trait]
+impl CommentFilter for KeywordFilter {
+    fn name(&self) -> &'static str {
+        "keywords"
+    }
+
+    async fn check(&self, note: &apub::Note, _author: &Account, _blog: &Blog) -> FilterVerdict {
+        let texts = [Some(note.content.as_str()), note.summary.as_deref()];
+        let found = self
+            .patterns
+            .iter()
+            .find(|(_, regex)| texts.iter().flatten().any(|text| regex.is_match(text)));
+        match found {
+            Some((keyword, _)) => FilterVerdict::caught(
+                self.action,
+                format!("Contains blocked keyword '{}'", keyword),
+            ),
+            None => FilterVerdict::Accept,
+        }
+    }
+}
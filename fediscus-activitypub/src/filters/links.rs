@@ -0,0 +1,54 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+
+use crate::apub;
+use crate::config::{FilterAction, LinkLimit};
+use crate::storage::{Account, Blog};
+
+use super::{CommentFilter, FilterVerdict};
+
+/// Catches comments with more links than allowed
+pub struct LinkFilter {
+    max_links: usize,
+    action: FilterAction,
+}
+
+impl LinkFilter {
+    pub fn new(config: &LinkLimit) -> Self {
+        Self {
+            max_links: config.max_links,
+            action: config.action,
+        }
+    }
+}
+
+#[async_trait]
+impl CommentFilter for LinkFilter {
+    fn name(&self) -> &'static str {
+        "links"
+    }
+
+    async fn check(&self, note: &apub::Note, _author: &Account, _blog: &Blog) -> FilterVerdict {
+        let links = match note.content_links() {
+            Ok(links) => links.len(),
+            // Content we can't parse is hardly a regular comment
+            Err(_) => {
+                return FilterVerdict::caught(self.action, "Content is not valid HTML".to_string())
+            }
+        };
+        if links > self.max_links {
+            FilterVerdict::caught(
+                self.action,
+                format!(
+                    "Contains {} links, at most {} are allowed",
+                    links, self.max_links
+                ),
+            )
+        } else {
+            FilterVerdict::Accept
+        }
+    }
+}
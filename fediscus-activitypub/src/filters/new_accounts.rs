@@ -0,0 +1,50 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::{Duration, Utc};
+
+use crate::apub;
+use crate::config::{AccountAge, FilterAction};
+use crate::storage::{Account, Blog};
+
+use super::{CommentFilter, FilterVerdict};
+
+/// Catches comments of accounts that were created on their instance only recently
+pub struct NewAccountFilter {
+    min_age: Duration,
+    action: FilterAction,
+}
+
+impl NewAccountFilter {
+    pub fn new(config: &AccountAge) -> Self {
+        Self {
+            min_age: Duration::seconds(config.min_age as i64),
+            action: config.action,
+        }
+    }
+}
+
+#[async_trait]
+impl CommentFilter for NewAccountFilter {
+    fn name(&self) -> &'static str {
+        "new-accounts"
+    }
+
+    async fn check(&self, _note: &apub::Note, author: &Account, _blog: &Blog) -> FilterVerdict {
+        // Not every instance publishes when its accounts were created
+        let Some(published) = author.published else {
+            return FilterVerdict::Accept;
+        };
+        let age = Utc::now().naive_utc() - published;
+        if age < self.min_age {
+            FilterVerdict::caught(
+                self.action,
+                format!("Account was created only {} hours ago", age.num_hours()),
+            )
+        } else {
+            FilterVerdict::Accept
+        }
+    }
+}
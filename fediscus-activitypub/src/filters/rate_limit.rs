@@ -0,0 +1,82 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+
+use crate::apub;
+use crate::config::{FilterAction, RateLimit};
+use crate::storage::{Account, AccountId, Blog};
+
+use super::{CommentFilter, CommentOrigin, FilterVerdict};
+
+/// Catches comments of accounts that have commented too often within the period
+///
+/// Comments are counted by when we received them, the publication time claimed by the remote
+/// server is not trusted. Only new comments delivered to our inbox are subject to the limit,
+/// and only those that passed this filter count towards it. The counts are kept in memory and
+/// start from zero whenever the server is restarted.
+pub struct RateLimitFilter {
+    max_comments: usize,
+    period: Duration,
+    action: FilterAction,
+    /// When the recent comments of each account have been received, oldest first
+    recent: Mutex<HashMap<AccountId, VecDeque<Instant>>>,
+}
+
+impl RateLimitFilter {
+    pub fn new(config: &RateLimit) -> Self {
+        Self {
+            max_comments: config.max_comments,
+            period: Duration::from_secs(config.period),
+            action: config.action,
+            recent: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+#[async_trait]
+impl CommentFilter for RateLimitFilter {
+    fn name(&self) -> &'static str {
+        "rate-limit"
+    }
+
+    fn applies_to(&self, origin: CommentOrigin) -> bool {
+        // A backfill imports a whole thread at once and an edit is no new comment, neither of
+        // them is a burst of activity
+        origin == CommentOrigin::Delivered
+    }
+
+    async fn check(&self, _note: &apub::Note, author: &Account, _blog: &Blog) -> FilterVerdict {
+        let now = Instant::now();
+        let mut recent = self.recent.lock().unwrap();
+        // Forget the comments that are out of the period, and the accounts that have none left
+        recent.retain(|_, times| {
+            while times
+                .front()
+                .is_some_and(|time| now.duration_since(*time) > self.period)
+            {
+                times.pop_front();
+            }
+            !times.is_empty()
+        });
+
+        let times = recent.entry(author.id).or_default();
+        if times.len() >= self.max_comments {
+            return FilterVerdict::caught(
+                self.action,
+                format!(
+                    "Account has posted {} comments in the last {} seconds",
+                    times.len(),
+                    self.period.as_secs()
+                ),
+            );
+        }
+        times.push_back(now);
+        FilterVerdict::Accept
+    }
+}
@@ -36,8 +36,12 @@ impl HttpServer {
 
         let service = axum::Router::new()
             .route("/users/:name", get(handlers::get_user))
+            .route("/inbox", post(handlers::post_inbox))
             .route("/users/:name/inbox", post(handlers::post_inbox))
             .route("/users/:name/outbox", get(handlers::get_outbox))
+            .route("/users/:name/followers", get(handlers::get_followers))
+            .route("/users/:name/following", get(handlers::get_following))
+            .route("/notes/:id", get(handlers::get_note))
             .route("/.well-known/webfinger", get(handlers::get_webfinger))
             .route_layer(FederationMiddleware::new(self.app_state.federation.clone()))
             .layer(TraceLayer::new_for_http())
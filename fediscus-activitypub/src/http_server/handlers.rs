@@ -8,25 +8,28 @@ use std::sync::Arc;
 
 use activitypub_federation::axum::inbox::{receive_activity, ActivityData};
 use activitypub_federation::config::Data;
+use activitypub_federation::error::Error as FederationError;
+use activitypub_federation::fetch::object_id::ObjectId;
 use activitypub_federation::fetch::webfinger::{Webfinger, WebfingerLink};
-use activitypub_federation::kinds::collection::OrderedCollectionType;
+use activitypub_federation::kinds::collection::{OrderedCollectionPageType, OrderedCollectionType};
 use activitypub_federation::traits::{ActivityHandler, Object};
 use activitypub_federation::{
     axum::json::FederationJson, protocol::context::WithContext, FEDERATION_CONTENT_TYPE,
 };
 use anyhow::Error;
-use axum::extract::{Query, State};
+use axum::body::{to_bytes, Body, Bytes};
+use axum::extract::{FromRequest, Query, Request, State};
 use axum::{
     extract::Path,
-    http::{HeaderMap, StatusCode},
-    response::IntoResponse,
+    http::{request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
 };
 use hyperx::header::{Accept, Header, Raw};
 use serde::{Deserialize, Serialize};
-use tracing::error;
+use tracing::{error, info};
 use url::Url;
 
-use crate::activities;
+use crate::activities::{self, ActivityError};
 use crate::apub;
 use crate::{storage, AppState, FederationData};
 
@@ -73,7 +76,14 @@ enum LocalUserAcceptedActivities {
     Create(activities::CreateNote),
     Like(apub::Like),
     UndoLike(apub::UndoLike),
+    Announce(apub::Announce),
+    UndoAnnounce(apub::UndoAnnounce),
     DeleteNote(apub::DeleteNote),
+    DeletePerson(apub::DeletePerson),
+    UpdateNote(apub::UpdateNote),
+    UpdatePerson(apub::UpdatePerson),
+    Move(apub::Move),
+    Flag(apub::Flag),
 }
 
 pub async fn get_user(
@@ -106,44 +116,168 @@ pub async fn get_user(
     }
 }
 
-pub async fn post_inbox(
-    State(_state): State<Arc<AppState>>,
-    data: Data<FederationData>,
-    activity_data: ActivityData,
-) -> impl IntoResponse {
+/// Maximum size of an incoming activity
+const MAX_ACTIVITY_SIZE: usize = 1024 * 1024;
+
+/// Just enough of an activity to find out who sent it
+#[derive(Deserialize)]
+struct ActivityActor {
+    actor: ObjectId<storage::Account>,
+}
+
+async fn receive(
+    parts: &Parts,
+    body: Bytes,
+    data: &Data<FederationData>,
+) -> Result<(), ActivityError> {
+    let mut request = Request::new(Body::from(body));
+    *request.method_mut() = parts.method.clone();
+    *request.uri_mut() = parts.uri.clone();
+    *request.headers_mut() = parts.headers.clone();
+    let activity_data = ActivityData::from_request(request, &())
+        .await
+        .map_err(|_| ActivityError::invalid_data("Failed to read activity"))?;
+
     receive_activity::<WithContext<LocalUserAcceptedActivities>, storage::Account, FederationData>(
         activity_data,
-        &data,
+        data,
     )
     .await
-    .map_err(|e| {
+}
+
+/// Serves both the inbox of the local actor and the shared inbox of the instance
+pub async fn post_inbox(
+    State(_state): State<Arc<AppState>>,
+    data: Data<FederationData>,
+    request: Request,
+) -> impl IntoResponse {
+    let (parts, body) = request.into_parts();
+    let body = to_bytes(body, MAX_ACTIVITY_SIZE)
+        .await
+        .map_err(|_| StatusCode::BAD_REQUEST)?;
+
+    // Drop activities of blocked actors and domains before doing any work on them
+    if let Ok(ActivityActor { actor }) = serde_json::from_slice::<ActivityActor>(&body) {
+        let blocked = data
+            .service
+            .storage()
+            .is_blocked(actor.inner())
+            .await
+            .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+        if blocked {
+            info!("Rejecting activity from blocked actor {}", actor);
+            return Err(StatusCode::FORBIDDEN);
+        }
+    }
+
+    let result = match receive(&parts, body.clone(), &data).await {
+        // The actor may have rotated their key since we've last fetched them, so fetch the
+        // actor again and give the activity one more chance with the new key.
+        Err(ActivityError::Federation {
+            source: FederationError::ActivitySignatureInvalid,
+            ..
+        }) => {
+            let actor = serde_json::from_slice::<ActivityActor>(&body)
+                .map_err(|_| StatusCode::BAD_REQUEST)?
+                .actor;
+            info!("Signature verification failed, refetching {}", actor);
+            match actor.dereference_forced(&data).await {
+                Ok(_) => receive(&parts, body, &data).await,
+                Err(e) => Err(ActivityError::storage(e, "Failed to refetch actor")),
+            }
+        }
+        result => result,
+    };
+
+    result.map_err(|e| {
         eprintln!("Error receiving activity: {:?}", e);
         StatusCode::INTERNAL_SERVER_ERROR
     })
 }
 
+/// Serves the notes published by our account
+pub async fn get_note(
+    Path((id,)): Path<(String,)>,
+    data: Data<FederationData>,
+) -> impl IntoResponse {
+    let uri = Url::parse(&format!(
+        "https://{}/notes/{}",
+        data.config.fediverse_user.host, id
+    ))
+    .map_err(|_| StatusCode::NOT_FOUND)?;
+    let note = data
+        .service
+        .storage()
+        .post_by_uri(&uri.into())
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+        .ok_or(StatusCode::NOT_FOUND)?;
+    if note.is_deleted() {
+        return Err(StatusCode::GONE);
+    }
+
+    let json = note
+        .into_json(&data)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    Ok(FederationJson(WithContext::new_default(json)).into_response())
+}
+
+/// Number of accounts per page of the followers and following collections
+const FOLLOWS_PAGE_SIZE: usize = 40;
+
+/// Number of activities per page of the outbox
+const OUTBOX_PAGE_SIZE: usize = 20;
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionQuery {
+    page: Option<usize>,
+}
+
 #[derive(Clone, Debug, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
-struct APubOutbox {
+struct APubCollection {
     id: Url,
     r#type: OrderedCollectionType,
-    total_items: i32,
-    ordered_items: Vec<()>,
+    total_items: usize,
+    /// Not set when the members are hidden
+    #[serde(skip_serializing_if = "Option::is_none")]
+    first: Option<Url>,
 }
 
-impl APubOutbox {
-    pub fn empty(id: Url) -> Self {
-        Self {
-            id,
-            r#type: OrderedCollectionType::OrderedCollection,
-            total_items: 0,
-            ordered_items: vec![],
-        }
-    }
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct APubCollectionPage<T> {
+    id: Url,
+    r#type: OrderedCollectionPageType,
+    part_of: Url,
+    total_items: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next: Option<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    prev: Option<Url>,
+    ordered_items: Vec<T>,
+}
+
+/// Returns the URL of the given page of the collection
+fn collection_page_url(id: &Url, page: usize) -> Url {
+    let mut url = id.clone();
+    url.query_pairs_mut().append_pair("page", &page.to_string());
+    url
+}
+
+/// Returns the offset of the first item on the given page of the collection, failing with
+/// `400 Bad Request` for pages too far out to be addressed.
+fn collection_page_offset(page: usize, page_size: usize) -> Result<usize, StatusCode> {
+    (page - 1)
+        .checked_mul(page_size)
+        .filter(|offset| i64::try_from(*offset).is_ok())
+        .ok_or(StatusCode::BAD_REQUEST)
 }
 
 pub async fn get_outbox(
     Path((name,)): Path<(String,)>,
+    Query(query): Query<CollectionQuery>,
     data: Data<FederationData>,
 ) -> impl IntoResponse {
     let local_user = get_local_user(&data)
@@ -154,18 +288,131 @@ pub async fn get_outbox(
         return Err(StatusCode::NOT_FOUND);
     }
 
-    match &local_user.outbox {
-        Some(outbox) => {
-            let outbox = APubOutbox::empty(outbox.clone().into());
-            Ok(FederationJson(WithContext::new_default(outbox)).into_response())
+    let Some(outbox) = &local_user.outbox else {
+        error!("Local account without valid outbox URL");
+        return Err(StatusCode::INTERNAL_SERVER_ERROR);
+    };
+    let id: Url = outbox.clone().into();
+
+    let storage = data.service.storage();
+    let total_items = storage
+        .outbox_count(local_user.id)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+
+    match query.page {
+        None => {
+            let collection = APubCollection {
+                id: id.clone(),
+                r#type: OrderedCollectionType::OrderedCollection,
+                total_items,
+                first: Some(collection_page_url(&id, 1)),
+            };
+            Ok(FederationJson(WithContext::new_default(collection)).into_response())
         }
+        Some(page) => {
+            let page = page.max(1);
+            let offset = collection_page_offset(page, OUTBOX_PAGE_SIZE)?;
+            let ordered_items = storage
+                .outbox_activities(local_user.id, offset, OUTBOX_PAGE_SIZE)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .into_iter()
+                .map(|activity| activity.activity.0)
+                .collect::<Vec<serde_json::Value>>();
+            let collection_page = APubCollectionPage {
+                id: collection_page_url(&id, page),
+                r#type: OrderedCollectionPageType::OrderedCollectionPage,
+                part_of: id.clone(),
+                total_items,
+                next: (offset + ordered_items.len() < total_items)
+                    .then(|| collection_page_url(&id, page + 1)),
+                prev: (page > 1).then(|| collection_page_url(&id, page - 1)),
+                ordered_items,
+            };
+            Ok(FederationJson(WithContext::new_default(collection_page)).into_response())
+        }
+    }
+}
+
+async fn get_follow_collection(
+    name: String,
+    page: Option<usize>,
+    direction: storage::FollowDirection,
+    data: &Data<FederationData>,
+) -> Result<Response, StatusCode> {
+    let local_user = get_local_user(data)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    if name != local_user.username {
+        return Err(StatusCode::NOT_FOUND);
+    }
+
+    let id = match direction {
+        storage::FollowDirection::Followers => local_user.followers_url(),
+        storage::FollowDirection::Following => local_user.following_url(),
+    }
+    .ok_or(StatusCode::INTERNAL_SERVER_ERROR)?;
+    let page_url = |page: usize| collection_page_url(&id, page);
+
+    let storage = data.service.storage();
+    let total_items = storage
+        .follow_count(local_user.id, direction)
+        .await
+        .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?;
+    let hidden = data.config.fediverse_user.hide_follows;
+
+    match page {
         None => {
-            error!("Local account without valid outbox URL");
-            Err(StatusCode::INTERNAL_SERVER_ERROR)
+            let collection = APubCollection {
+                id: id.clone(),
+                r#type: OrderedCollectionType::OrderedCollection,
+                total_items,
+                first: (!hidden).then(|| page_url(1)),
+            };
+            Ok(FederationJson(WithContext::new_default(collection)).into_response())
+        }
+        Some(_) if hidden => Err(StatusCode::NOT_FOUND),
+        Some(page) => {
+            let page = page.max(1);
+            let offset = collection_page_offset(page, FOLLOWS_PAGE_SIZE)?;
+            let ordered_items = storage
+                .follow_account_uris(local_user.id, direction, offset, FOLLOWS_PAGE_SIZE)
+                .await
+                .map_err(|_| StatusCode::INTERNAL_SERVER_ERROR)?
+                .into_iter()
+                .map(Into::into)
+                .collect::<Vec<Url>>();
+            let collection_page = APubCollectionPage {
+                id: page_url(page),
+                r#type: OrderedCollectionPageType::OrderedCollectionPage,
+                part_of: id.clone(),
+                total_items,
+                next: (offset + ordered_items.len() < total_items).then(|| page_url(page + 1)),
+                prev: (page > 1).then(|| page_url(page - 1)),
+                ordered_items,
+            };
+            Ok(FederationJson(WithContext::new_default(collection_page)).into_response())
         }
     }
 }
 
+pub async fn get_followers(
+    Path((name,)): Path<(String,)>,
+    Query(query): Query<CollectionQuery>,
+    data: Data<FederationData>,
+) -> impl IntoResponse {
+    get_follow_collection(name, query.page, storage::FollowDirection::Followers, &data).await
+}
+
+pub async fn get_following(
+    Path((name,)): Path<(String,)>,
+    Query(query): Query<CollectionQuery>,
+    data: Data<FederationData>,
+) -> impl IntoResponse {
+    get_follow_collection(name, query.page, storage::FollowDirection::Following, &data).await
+}
+
 #[derive(Debug, Deserialize)]
 pub struct WebFingerQuery {
     resource: String,
@@ -0,0 +1,13 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Background jobs that run outside of the regular inbox processing
+
+mod backfill;
+mod queue;
+mod refresh;
+
+pub use backfill::{backfill_blog, backfill_thread, spawn_backfill_thread};
+pub use queue::{run_pending_jobs, spawn_job_runner};
+pub use refresh::{refresh_account, refresh_accounts, spawn_account_refresh};
@@ -0,0 +1,297 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Imports replies that were written before we learned about a thread by walking the
+//! `replies` collections of its notes.
+
+use std::collections::VecDeque;
+
+use activitypub_federation::config::Data;
+use activitypub_federation::fetch::fetch_object_http;
+use activitypub_federation::protocol::verification::verify_domains_match;
+use serde::de::DeserializeOwned;
+use tracing::{info, instrument, warn};
+use url::Url;
+
+use crate::activities::ActivityError;
+use crate::apub::{self, Collection, CollectionPage, ObjectOrLink};
+use crate::filters::CommentOrigin;
+use crate::storage::{ThreadOrder, ThreadPage};
+use crate::FederationData;
+
+/// Maximum number of remote objects (notes and collection pages) fetched during a single
+/// backfill, so that a huge or malicious thread can't keep us busy forever.
+const MAX_FETCHES: usize = 1000;
+
+/// Keeps track of the number of remote fetches performed by the backfill
+struct Budget(usize);
+
+impl Budget {
+    fn take(&mut self) -> bool {
+        if self.0 == 0 {
+            return false;
+        }
+        self.0 -= 1;
+        true
+    }
+}
+
+async fn fetch<Kind: DeserializeOwned>(
+    url: &Url,
+    data: &Data<FederationData>,
+) -> Result<Kind, ActivityError> {
+    // Each backfill fetch gets a fresh request counter, the total is limited by the budget
+    fetch_object_http::<_, Kind>(url, &data.reset_request_count())
+        .await
+        .map(|response| response.object)
+        .map_err(|e| ActivityError::federation(e, format!("Failed to fetch {}", url)))
+}
+
+/// Returns the reply itself if it comes from the same origin as the page it was embedded in,
+/// otherwise fetches it from its origin.
+async fn resolve_reply(
+    item: ObjectOrLink<apub::Note>,
+    origin: &Url,
+    data: &Data<FederationData>,
+    budget: &mut Budget,
+) -> Result<Option<apub::Note>, ActivityError> {
+    let url = match item {
+        ObjectOrLink::Object(note) if verify_domains_match(note.id.inner(), origin).is_ok() => {
+            return Ok(Some(note));
+        }
+        ObjectOrLink::Object(note) => note.id.into_inner(),
+        ObjectOrLink::Link(url) => url,
+    };
+
+    if !budget.take() {
+        return Ok(None);
+    }
+    let note: apub::Note = fetch(&url, data).await?;
+    verify_domains_match(note.id.inner(), &url)
+        .map_err(|e| ActivityError::verification(e, "Reply ID does not match its origin"))?;
+    Ok(Some(note))
+}
+
+/// Returns all direct replies of the note listed in its `replies` collection
+async fn fetch_replies(
+    note: &apub::Note,
+    data: &Data<FederationData>,
+    budget: &mut Budget,
+) -> Result<Vec<apub::Note>, ActivityError> {
+    let origin = note.id.inner();
+    let collection = match &note.replies {
+        None => return Ok(Vec::new()),
+        Some(ObjectOrLink::Object(collection)) => collection.clone(),
+        Some(ObjectOrLink::Link(url)) => {
+            if !budget.take() {
+                return Ok(Vec::new());
+            }
+            fetch::<Collection>(url, data).await?
+        }
+    };
+
+    let mut items = collection.items;
+    let mut next_page = collection.first;
+    while let Some(page) = next_page.take() {
+        let page = match page {
+            ObjectOrLink::Object(page) => page,
+            ObjectOrLink::Link(url) => {
+                if !budget.take() {
+                    break;
+                }
+                fetch::<CollectionPage>(&url, data).await?
+            }
+        };
+        items.extend(page.items);
+        next_page = page.next.map(ObjectOrLink::Link);
+    }
+
+    let mut replies = Vec::with_capacity(items.len());
+    for item in items {
+        match resolve_reply(item, origin, data, budget).await {
+            Ok(Some(reply)) => replies.push(reply),
+            Ok(None) => break,
+            Err(e) => warn!("Failed to resolve reply of {}: {}", origin, e),
+        }
+    }
+    Ok(replies)
+}
+
+/// Imports all replies to the given root note, including replies to replies.
+///
+/// Returns the number of newly imported notes.
+#[instrument(skip_all, fields(root=%root.id.inner()))]
+pub async fn backfill_thread(
+    root: apub::Note,
+    data: &Data<FederationData>,
+) -> Result<usize, ActivityError> {
+    let storage = data.service.storage();
+    let mut budget = Budget(MAX_FETCHES);
+    let mut imported = 0;
+    let mut queue = VecDeque::from([root]);
+
+    while let Some(note) = queue.pop_front() {
+        // A note whose replies can't be listed only ends its own branch of the thread
+        let replies = match fetch_replies(&note, data, &mut budget).await {
+            Ok(replies) => replies,
+            Err(e) => {
+                warn!("Failed to fetch replies of {}: {}", note.id.inner(), e);
+                continue;
+            }
+        };
+        for reply in replies {
+            let known = storage
+                .post_by_uri(&reply.id.inner().clone().into())
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to look up reply"))?;
+            if known.is_none() {
+                let data = data.reset_request_count();
+                let stored = match reply.author(&data).await {
+                    Ok(account) => {
+                        reply
+                            .process_new_note(&data, &account, CommentOrigin::Imported)
+                            .await
+                    }
+                    Err(e) => Err(e),
+                };
+                match stored {
+                    Ok(Some(_)) => imported += 1,
+                    Ok(None) => continue,
+                    Err(e) => {
+                        warn!("Failed to import reply {}: {}", reply.id.inner(), e);
+                        continue;
+                    }
+                }
+            }
+            queue.push_back(reply);
+        }
+    }
+
+    info!("Imported {} replies", imported);
+    Ok(imported)
+}
+
+/// Runs [`backfill_thread`] in the background
+pub fn spawn_backfill_thread(root: apub::Note, data: &Data<FederationData>) {
+    let data = data.reset_request_count();
+    tokio::spawn(async move {
+        if let Err(e) = backfill_thread(root, &data).await {
+            warn!("Failed to backfill thread: {}", e);
+        }
+    });
+}
+
+/// Re-runs the backfill for all threads discussing the given blog post.
+///
+/// Returns the number of newly imported notes.
+pub async fn backfill_blog(url: &Url, data: &Data<FederationData>) -> Result<usize, ActivityError> {
+    let storage = data.service.storage();
+    let blog = storage
+        .blog_by_url(url)
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to look up blog"))?
+        .ok_or_else(|| ActivityError::invalid_data(format!("Unknown blog {}", url)))?;
+    let thread = storage
+        .thread_by_blog_id(blog.id, ThreadOrder::Oldest, ThreadPage::default())
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to load threads"))?;
+
+    let mut imported = 0;
+    for root in thread
+        .notes
+        .into_iter()
+        .filter(|n| n.note.reply_to_id.is_none())
+    {
+        let json: apub::Note = match fetch(root.note.uri.as_url(), data).await {
+            Ok(json) => json,
+            Err(e) => {
+                warn!("Failed to fetch thread root {}: {}", root.note.uri, e);
+                continue;
+            }
+        };
+        imported += backfill_thread(json, data).await?;
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{AccountStorage, BlogStorage, ModerationState, NoteContent, NoteStorage};
+    use crate::testing::server::{listen, new_instance, DbPost};
+    use crate::testing::{create_person, federation_config, MemoryStorage};
+
+    #[tokio::test]
+    async fn test_backfill_blog() {
+        let test_server = new_instance("localhost:8090", "testuser".to_string())
+            .await
+            .unwrap();
+        listen(&test_server).unwrap();
+        let user = test_server.local_user();
+        let request_data = test_server.to_request_data();
+        let post = DbPost::new(
+            "My new post! https://example.com/blog-post #fediscus".to_string(),
+            user.ap_id.clone(),
+        )
+        .unwrap();
+        user.post(post.clone(), &request_data).await.unwrap();
+        let reply = DbPost::new_reply("First!".to_string(), user.ap_id.clone(), post.ap_id.clone())
+            .unwrap();
+        user.post(reply.clone(), &request_data).await.unwrap();
+        let nested = DbPost::new_reply(
+            "Second!".to_string(),
+            user.ap_id.clone(),
+            reply.ap_id.clone(),
+        )
+        .unwrap();
+        user.post(nested.clone(), &request_data).await.unwrap();
+
+        // We only know about the root note
+        let blog_url = Url::parse("https://example.com/blog-post").unwrap();
+        let storage = MemoryStorage::new("example.com");
+        let account = storage
+            .new_account(&create_person("testuser", "localhost:8090"))
+            .await
+            .unwrap();
+        let blog = storage.new_blog(&blog_url).await.unwrap();
+        let root = storage
+            .new_post(
+                account.id,
+                post.ap_id.inner().clone().into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
+            .await
+            .unwrap();
+
+        let federation = federation_config(storage).await;
+        let data = federation.to_request_data();
+        let imported = backfill_blog(&blog_url, &data).await.unwrap();
+        assert_eq!(imported, 2);
+
+        let storage = data.service.storage();
+        let reply = storage
+            .post_by_uri(&reply.ap_id.inner().clone().into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(reply.reply_to_id, Some(root.id));
+        assert_eq!(reply.blog_id, blog.id);
+        let nested = storage
+            .post_by_uri(&nested.ap_id.inner().clone().into())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(nested.reply_to_id, Some(reply.id));
+        assert_eq!(nested.root_id, Some(root.id));
+
+        // Running the backfill again doesn't import anything new
+        let imported = backfill_blog(&blog_url, &data).await.unwrap();
+        assert_eq!(imported, 0);
+        assert_eq!(storage.post_count().await.unwrap(), 3);
+    }
+}
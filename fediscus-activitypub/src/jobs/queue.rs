@@ -0,0 +1,117 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Runs jobs requested through the admin API. The API lives in a separate process, so the
+//! jobs are handed over through the database.
+
+use std::time::Duration;
+
+use activitypub_federation::config::Data;
+use tracing::{info, instrument, warn};
+use url::Url;
+
+use crate::activities::ActivityError;
+use crate::storage::{Job, JobKind};
+use crate::FederationData;
+
+use super::{backfill_blog, refresh_account};
+
+/// Runs all jobs that have not been started yet, oldest first. Failed jobs are recorded along
+/// with their error and are not retried.
+///
+/// Returns the number of jobs that have been run.
+#[instrument(skip_all)]
+pub async fn run_pending_jobs(data: &Data<FederationData>) -> Result<usize, ActivityError> {
+    let storage = data.service.storage();
+    let mut processed = 0;
+    while let Some(job) = storage
+        .start_next_job()
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to look up pending jobs"))?
+    {
+        let error = match run_job(&job, data).await {
+            Ok(()) => None,
+            Err(e) => {
+                warn!("Job {:?} for {} failed: {}", job.kind, job.target, e);
+                Some(e.to_string())
+            }
+        };
+        storage
+            .finish_job(job.id, error.as_deref())
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to finish job"))?;
+        processed += 1;
+    }
+    Ok(processed)
+}
+
+async fn run_job(job: &Job, data: &Data<FederationData>) -> Result<(), ActivityError> {
+    let target = Url::parse(&job.target).map_err(|e| {
+        ActivityError::invalid_data(format!("Invalid job target {}: {}", job.target, e))
+    })?;
+    match job.kind {
+        JobKind::Backfill => {
+            let imported = backfill_blog(&target, data).await?;
+            info!("Imported {} notes for {}", imported, target);
+        }
+        JobKind::RefreshAccount => {
+            if !refresh_account(&target.into(), data).await? {
+                info!("Account {} no longer exists", job.target);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Runs [`run_pending_jobs`] in the background at the configured interval.
+pub fn spawn_job_runner(data: &Data<FederationData>) {
+    let data = data.reset_request_count();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            data.config.federation.job_poll_interval,
+        ));
+        loop {
+            interval.tick().await;
+            match run_pending_jobs(&data).await {
+                Ok(0) => {}
+                Ok(processed) => info!("Ran {} jobs", processed),
+                Err(e) => warn!("Failed to run jobs: {}", e),
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::JobStorage;
+    use crate::testing::{federation_config, MemoryStorage};
+
+    #[tokio::test]
+    async fn test_failed_jobs_are_recorded() {
+        let storage = MemoryStorage::new("example.com");
+        let invalid = storage
+            .new_job(JobKind::RefreshAccount, "not a URL")
+            .await
+            .unwrap();
+        let unknown = storage
+            .new_job(JobKind::Backfill, "https://example.com/unknown-post")
+            .await
+            .unwrap();
+        let federation = federation_config(storage).await;
+        let data = federation.to_request_data();
+
+        assert_eq!(run_pending_jobs(&data).await.unwrap(), 2);
+        // Finished jobs are not run again
+        assert_eq!(run_pending_jobs(&data).await.unwrap(), 0);
+
+        let storage = data.service.storage();
+        for id in [invalid.id, unknown.id] {
+            let job = storage.job_by_id(id).await.unwrap().unwrap();
+            assert!(job.started_at.is_some());
+            assert!(job.finished_at.is_some());
+            assert!(job.error.is_some());
+        }
+    }
+}
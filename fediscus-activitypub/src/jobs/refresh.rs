@@ -0,0 +1,80 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+//! Periodically re-fetches remote actors, so that changed keys, inboxes or names are picked up
+//! even when their instance never sends us an `Update`.
+
+use std::time::Duration;
+
+use activitypub_federation::config::Data;
+use activitypub_federation::error::Error as FederationError;
+use activitypub_federation::fetch::object_id::ObjectId;
+use chrono::{TimeDelta, Utc};
+use tracing::{info, instrument, warn};
+
+use crate::activities::ActivityError;
+use crate::db::Uri;
+use crate::storage::{Account, AccountError};
+use crate::FederationData;
+
+/// Re-fetches all remote accounts that have not been updated for longer than the configured
+/// maximum age. Accounts that are gone from their instance are purged.
+///
+/// Returns the number of refreshed accounts.
+#[instrument(skip_all)]
+pub async fn refresh_accounts(data: &Data<FederationData>) -> Result<usize, ActivityError> {
+    let max_age = TimeDelta::seconds(data.config.federation.actor_max_age as i64);
+    let accounts = data
+        .service
+        .storage()
+        .accounts_updated_before(Utc::now().naive_utc() - max_age)
+        .await
+        .map_err(|e| ActivityError::storage(e, "Failed to look up stale accounts"))?;
+
+    let mut refreshed = 0;
+    for account in accounts {
+        match refresh_account(&account.uri, data).await {
+            Ok(true) => refreshed += 1,
+            Ok(false) => {}
+            Err(e) => warn!("Failed to refresh account {}: {}", account.uri, e),
+        }
+    }
+    Ok(refreshed)
+}
+
+/// Re-fetches a single remote account, the account is purged if it's gone from its instance.
+///
+/// Returns whether the account still exists.
+pub async fn refresh_account(
+    uri: &Uri,
+    data: &Data<FederationData>,
+) -> Result<bool, ActivityError> {
+    let id: ObjectId<Account> = uri.clone().into();
+    match id.dereference_forced(&data.reset_request_count()).await {
+        Ok(_) => Ok(true),
+        Err(AccountError::UrlVerificationError(FederationError::ObjectDeleted(_))) => {
+            info!("Account {} is gone, purging it", uri);
+            data.service.delete_account(uri.clone()).await?;
+            Ok(false)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Runs [`refresh_accounts`] in the background at the configured interval.
+pub fn spawn_account_refresh(data: &Data<FederationData>) {
+    let data = data.reset_request_count();
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(
+            data.config.federation.actor_refresh_interval,
+        ));
+        loop {
+            interval.tick().await;
+            match refresh_accounts(&data).await {
+                Ok(refreshed) => info!("Refreshed {} remote accounts", refreshed),
+                Err(e) => warn!("Failed to refresh remote accounts: {}", e),
+            }
+        }
+    });
+}
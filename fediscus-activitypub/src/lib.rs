@@ -6,7 +6,9 @@ mod activities;
 mod apub;
 mod config;
 pub mod db;
+pub mod filters;
 mod http_server;
+pub mod jobs;
 mod service;
 mod sqlite;
 mod storage;
@@ -22,11 +24,18 @@ pub struct AppState {
 pub struct FederationData {
     pub config: Config,
     pub service: Arc<Box<dyn ActivityPubService + Send + Sync + 'static>>,
+    /// The filters new comments go through before they are stored
+    pub filters: Arc<filters::FilterChain>,
     //pub storage: Arc<Box<dyn Storage + Send + Sync + 'static>>,
 }
 
 pub use config::Config;
 pub use http_server::HttpServer;
 pub use service::ActivityPubService;
+pub use service::BlocklistVerifier;
 pub use service::Service;
 pub use sqlite::SqliteStorage; // FIXME: this leaks abstraction
+pub use storage::{
+    BlogId, ModerationPolicy, ModerationState, NoteError, NoteId, NoteStorage, Thread, ThreadNote,
+    ThreadOrder, ThreadPage,
+};
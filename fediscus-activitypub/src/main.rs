@@ -4,14 +4,21 @@
 // SPDX-License-Identifier: MIT
 
 use activitypub_federation::config::FederationConfig;
-use anyhow::Error;
+use anyhow::{anyhow, Error};
 use fediscus_activitypub::ActivityPubService;
+use fediscus_activitypub::BlocklistVerifier;
 use std::sync::Arc;
+use tracing::info;
+use url::Url;
 
 use fediscus_activitypub::db;
+use fediscus_activitypub::filters::FilterChain;
+use fediscus_activitypub::jobs;
 use fediscus_activitypub::Config;
 use fediscus_activitypub::FederationData;
 use fediscus_activitypub::HttpServer;
+use fediscus_activitypub::ModerationPolicy;
+use fediscus_activitypub::ModerationState;
 use fediscus_activitypub::Service;
 use fediscus_activitypub::SqliteStorage;
 
@@ -35,14 +42,145 @@ async fn main() -> Result<(), Error> {
     let federation_data = FederationData {
         config: config.clone(),
         service: Arc::clone(&service),
+        filters: Arc::new(FilterChain::from_config(&config.filters)?),
     };
 
     let federation = FederationConfig::builder()
         .domain(config.fediverse_user.host.clone())
         .app_data(federation_data)
+        .url_verifier(Box::new(BlocklistVerifier::new(Arc::clone(&service))))
         .build()
         .await?;
 
-    let http_server = HttpServer::new(&config, federation).await?;
-    http_server.run().await
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => {
+            jobs::spawn_account_refresh(&federation.to_request_data());
+            jobs::spawn_job_runner(&federation.to_request_data());
+            let http_server = HttpServer::new(&config, federation).await?;
+            http_server.run().await
+        }
+        ["backfill", blog_url] => {
+            let url = Url::parse(blog_url)?;
+            let imported = jobs::backfill_blog(&url, &federation.to_request_data()).await?;
+            info!("Imported {} notes for {}", imported, url);
+            Ok(())
+        }
+        ["announce", post_url, title] => {
+            let url = Url::parse(post_url)?;
+            let note = service
+                .announce_blog_post(
+                    url.clone(),
+                    title.to_string(),
+                    &federation.to_request_data(),
+                )
+                .await?;
+            info!("Announced {} as {}", url, note.uri);
+            // Wait for the delivery to the followers before exiting
+            federation.shutdown(false).await?;
+            Ok(())
+        }
+        ["block-domain", domain, rest @ ..] if matches!(rest, [] | ["--purge"]) => {
+            let block = service.block_domain(domain, None, !rest.is_empty()).await?;
+            info!("Blocked domain {}", block.domain);
+            Ok(())
+        }
+        ["unblock-domain", domain] => {
+            service.unblock_domain(domain).await?;
+            info!("Unblocked domain {}", domain);
+            Ok(())
+        }
+        ["block-actor", actor_uri] => {
+            let uri = Url::parse(actor_uri)?;
+            service.storage().new_actor_block(&uri.into(), None).await?;
+            info!("Blocked actor {}", actor_uri);
+            Ok(())
+        }
+        ["unblock-actor", actor_uri] => {
+            let uri = Url::parse(actor_uri)?;
+            service.storage().delete_actor_block(&uri.into()).await?;
+            info!("Unblocked actor {}", actor_uri);
+            Ok(())
+        }
+        ["moderation-queue"] => {
+            let pending = service
+                .storage()
+                .posts_by_moderation_state(ModerationState::Pending)
+                .await?;
+            for note in &pending {
+                info!("{}: {}", note.uri, note.content);
+            }
+            info!("{} comments awaiting moderation", pending.len());
+            Ok(())
+        }
+        [action @ ("approve" | "reject" | "hide"), note_uri] => {
+            let state = match *action {
+                "approve" => ModerationState::Approved,
+                "reject" => ModerationState::Rejected,
+                _ => ModerationState::Hidden,
+            };
+            let note = service
+                .moderate_note(Url::parse(note_uri)?.into(), state)
+                .await?;
+            info!("Comment {} is now {:?}", note.uri, note.moderation_state);
+            Ok(())
+        }
+        ["moderation-policy", blog_url, policy] => {
+            let policy = match *policy {
+                "auto-approve" => ModerationPolicy::AutoApprove,
+                "approve-known" => ModerationPolicy::ApproveKnown,
+                "hold-all" => ModerationPolicy::HoldAll,
+                _ => return Err(anyhow!("Unknown moderation policy '{}'", policy)),
+            };
+            let blog = service
+                .set_moderation_policy(&Url::parse(blog_url)?, policy)
+                .await?;
+            info!("Comments on {} are now moderated as {:?}", blog.url, policy);
+            Ok(())
+        }
+        ["reports"] => {
+            let reports = service.storage().unresolved_reports().await?;
+            for report in &reports {
+                let objects: Vec<_> = report.objects.iter().map(Url::as_str).collect();
+                info!(
+                    "{}: {} ({})",
+                    report.uri,
+                    objects.join(", "),
+                    report.content.as_deref().unwrap_or_default()
+                );
+            }
+            info!("{} reports awaiting review", reports.len());
+            Ok(())
+        }
+        ["report", rest @ ..] if !rest.is_empty() => {
+            let (note_uris, comment) = match rest {
+                [note_uris @ .., "--comment", comment] => (note_uris, Some(comment.to_string())),
+                note_uris => (note_uris, None),
+            };
+            let note_uris = note_uris
+                .iter()
+                .map(|uri| Url::parse(uri).map(Into::into))
+                .collect::<Result<Vec<_>, _>>()?;
+            let sent = service
+                .report_notes(note_uris, comment, &federation.to_request_data())
+                .await?;
+            info!("Sent {} reports", sent);
+            // Wait for the delivery of the reports before exiting
+            federation.shutdown(false).await?;
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "Usage: fediscus-activitypub [backfill <blog-url> | announce <post-url> <title> | \
+             block-domain <domain> [--purge] | unblock-domain <domain> | \
+             block-actor <actor-uri> | unblock-actor <actor-uri> | moderation-queue | \
+             approve|reject|hide <note-uri> | \
+             moderation-policy <blog-url> auto-approve|approve-known|hold-all | reports | \
+             report <note-uri>... [--comment <text>]]"
+        )),
+    }
 }
@@ -1,5 +1,7 @@
 mod activitypubservice;
+mod blocklist;
 mod service_impl;
 
 pub use activitypubservice::ActivityPubService;
+pub use blocklist::BlocklistVerifier;
 pub use service_impl::Service;
@@ -1,9 +1,12 @@
 use crate::activities::ActivityError;
-use crate::storage::{Account, NoteError, Storage};
+use crate::storage::{
+    Account, Blog, DomainBlock, ModerationPolicy, ModerationState, Note, NoteError, Storage,
+};
 use crate::FederationData;
 use crate::{apub::Follow, db::Uri};
 use activitypub_federation::config::Data;
 use async_trait::async_trait;
+use url::Url;
 
 #[async_trait]
 pub trait ActivityPubService: Send + Sync + 'static {
@@ -27,9 +30,92 @@ pub trait ActivityPubService: Send + Sync + 'static {
         data: &Data<FederationData>,
     ) -> Result<(), ActivityError>;
 
-    async fn like_post(&self, post_uri: Uri) -> Result<(), ActivityError>;
+    /// Records a like of the given post by the account, each account is counted only once
+    async fn like_post(
+        &self,
+        post_uri: Uri,
+        account: &Account,
+        like_uri: Uri,
+    ) -> Result<(), ActivityError>;
 
-    async fn unlike_post(&self, post_uri: Uri) -> Result<(), ActivityError>;
+    /// Removes the account's like of the given post
+    async fn unlike_post(&self, post_uri: Uri, account: &Account) -> Result<(), ActivityError>;
+
+    /// Records a repost of the given post by the account, each account is counted only once
+    async fn repost_post(
+        &self,
+        post_uri: Uri,
+        account: &Account,
+        repost_uri: Uri,
+    ) -> Result<(), ActivityError>;
+
+    /// Removes the account's repost of the given post
+    async fn unrepost_post(&self, post_uri: Uri, account: &Account) -> Result<(), ActivityError>;
 
     async fn delete_note(&self, note_uri: Uri) -> Result<(), NoteError>;
+
+    /// Removes a remote account that has been deleted, together with its notes and follows
+    async fn delete_account(&self, account_uri: Uri) -> Result<(), ActivityError>;
+
+    /// Transfers the follows of an account that has migrated to its new account and follows
+    /// the new account if we followed the old one
+    async fn move_account(
+        &self,
+        from: Account,
+        to: Account,
+        data: &Data<FederationData>,
+    ) -> Result<(), ActivityError>;
+
+    /// Publishes a note about a new blog post to the followers of our account, the note
+    /// becomes the root of the blog post's thread
+    async fn announce_blog_post(
+        &self,
+        url: Url,
+        title: String,
+        data: &Data<FederationData>,
+    ) -> Result<Note, ActivityError>;
+
+    /// Blocks the domain and all its subdomains, optionally purging all accounts from the
+    /// domain along with their comments
+    async fn block_domain(
+        &self,
+        domain: &str,
+        reason: Option<&str>,
+        purge: bool,
+    ) -> Result<DomainBlock, ActivityError>;
+
+    async fn unblock_domain(&self, domain: &str) -> Result<(), ActivityError>;
+
+    /// Approves, rejects or hides a comment, only approved comments are shown publicly
+    async fn moderate_note(
+        &self,
+        note_uri: Uri,
+        state: ModerationState,
+    ) -> Result<Note, ActivityError>;
+
+    /// Sets how new replies in the threads about the blog post are moderated
+    async fn set_moderation_policy(
+        &self,
+        blog_url: &Url,
+        policy: ModerationPolicy,
+    ) -> Result<Blog, ActivityError>;
+
+    /// Stores a report of our content sent by another instance, so that it can be reviewed
+    async fn handle_report(
+        &self,
+        account: &Account,
+        report_uri: Uri,
+        content: Option<String>,
+        objects: Vec<Url>,
+    ) -> Result<(), ActivityError>;
+
+    /// Reports the notes to the instances of their authors, sending one report per author.
+    ///
+    /// Returns the number of reports sent.
+    async fn report_notes(
+        &self,
+        note_uris: Vec<Uri>,
+        comment: Option<String>,
+        data: &Data<FederationData>,
+    ) -> Result<usize, ActivityError>;
 }
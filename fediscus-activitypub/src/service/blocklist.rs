@@ -0,0 +1,43 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use std::sync::Arc;
+
+use activitypub_federation::config::UrlVerifier;
+use activitypub_federation::error::Error as FederationError;
+use async_trait::async_trait;
+use tracing::{error, info};
+use url::Url;
+
+use crate::service::ActivityPubService;
+
+/// Rejects URLs of blocked domains and actors, both when fetching remote objects and when
+/// receiving activities
+#[derive(Clone)]
+pub struct BlocklistVerifier {
+    service: Arc<Box<dyn ActivityPubService + Send + Sync + 'static>>,
+}
+
+impl BlocklistVerifier {
+    pub fn new(service: Arc<Box<dyn ActivityPubService + Send + Sync + 'static>>) -> Self {
+        Self { service }
+    }
+}
+
+#[async_trait]
+impl UrlVerifier for BlocklistVerifier {
+    async fn verify(&self, url: &Url) -> Result<(), FederationError> {
+        match self.service.storage().is_blocked(url).await {
+            Ok(false) => Ok(()),
+            Ok(true) => {
+                info!("Rejecting blocked URL {}", url);
+                Err(FederationError::Other(format!("{} is blocked", url)))
+            }
+            Err(e) => {
+                error!("Failed to check blocklist: {}", e);
+                Err(FederationError::Other("Failed to check blocklist".into()))
+            }
+        }
+    }
+}
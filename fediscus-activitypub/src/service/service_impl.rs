@@ -1,12 +1,16 @@
-use crate::activities::ActivityError;
-use crate::apub::{AcceptFollow, Follow, UndoFollow};
+use crate::activities::{blog_for_url, generate_note_id, ActivityError, CreateNote};
+use crate::apub::{self, AcceptFollow, Flag, Follow, UndoFollow};
 use crate::db::Uri;
-use crate::storage::{Account, NoteError, Storage};
+use crate::storage::{
+    is_subdomain, normalize_domain, Account, AccountError, AccountId, Blog, DomainBlock,
+    ModerationPolicy, ModerationState, Note, NoteContent, NoteError, Storage,
+};
 use crate::FederationData;
 use activitypub_federation::config::Data;
 use activitypub_federation::traits::{Actor, Object};
 use async_trait::async_trait;
 use tracing::info;
+use url::Url;
 
 use crate::service::ActivityPubService;
 
@@ -138,36 +142,348 @@ impl ActivityPubService for Service {
         Ok(())
     }
 
-    async fn like_post(&self, post_uri: Uri) -> Result<(), ActivityError> {
+    async fn like_post(
+        &self,
+        post_uri: Uri,
+        account: &Account,
+        like_uri: Uri,
+    ) -> Result<(), ActivityError> {
+        let Some(post) = self
+            .storage
+            .post_by_uri(&post_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find post"))?
+        else {
+            info!("Like: note not found");
+            return Ok(());
+        };
+
         self.storage
-            .like_post(&post_uri)
+            .new_like(post.id, account.id, &like_uri)
             .await
-            .map_err(|e| ActivityError::storage(e, "Failed to like post"))
+            .map_err(|e| ActivityError::storage(e, "Failed to like post"))?;
+        Ok(())
     }
 
-    async fn unlike_post(&self, post_uri: Uri) -> Result<(), ActivityError> {
+    async fn unlike_post(&self, post_uri: Uri, account: &Account) -> Result<(), ActivityError> {
+        let Some(post) = self
+            .storage
+            .post_by_uri(&post_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find post"))?
+        else {
+            info!("UndoLike: note not found");
+            return Ok(());
+        };
+
         self.storage
-            .unlike_post(&post_uri)
+            .delete_like(post.id, account.id)
             .await
             .map_err(|e| ActivityError::storage(e, "Failed to unlike post"))
     }
 
+    async fn repost_post(
+        &self,
+        post_uri: Uri,
+        account: &Account,
+        repost_uri: Uri,
+    ) -> Result<(), ActivityError> {
+        let Some(post) = self
+            .storage
+            .post_by_uri(&post_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find post"))?
+        else {
+            info!("Announce: note not found");
+            return Ok(());
+        };
+
+        self.storage
+            .new_repost(post.id, account.id, &repost_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to repost post"))?;
+        Ok(())
+    }
+
+    async fn unrepost_post(&self, post_uri: Uri, account: &Account) -> Result<(), ActivityError> {
+        let Some(post) = self
+            .storage
+            .post_by_uri(&post_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find post"))?
+        else {
+            info!("UndoAnnounce: note not found");
+            return Ok(());
+        };
+
+        self.storage
+            .delete_repost(post.id, account.id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to unrepost post"))
+    }
+
     async fn delete_note(&self, note_uri: Uri) -> Result<(), NoteError> {
         let post = self.storage.post_by_uri(&note_uri).await?;
         if let Some(post) = post {
-            self.storage.delete_post_by_id(post.id).await?;
+            // Keep the note as a tombstone so that replies to it stay in the thread
+            self.storage.tombstone_post(post.id).await?;
         } else {
             info!("DeleteNote: note not found");
         }
         Ok(())
     }
+
+    async fn delete_account(&self, account_uri: Uri) -> Result<(), ActivityError> {
+        let Some(account) = self.storage.account_by_uri(&account_uri).await? else {
+            info!("DeletePerson: account not found");
+            return Ok(());
+        };
+        if account.local {
+            return Err(ActivityError::invalid_data(
+                "Refusing to delete the local account",
+            ));
+        }
+
+        self.storage
+            .purge_account(account.id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to purge account"))
+    }
+
+    async fn move_account(
+        &self,
+        from: Account,
+        to: Account,
+        data: &Data<FederationData>,
+    ) -> Result<(), ActivityError> {
+        if from.local || to.local {
+            return Err(ActivityError::invalid_data(
+                "Refusing to move the local account",
+            ));
+        }
+
+        let local = self.storage.get_local_account().await?;
+        let followed_back = self
+            .storage
+            .follow_by_ids(local.id, from.id)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to find follow back"))?
+            .is_some();
+
+        self.storage.move_account(from.id, to.id).await?;
+
+        // We can't just take over the follow of the old account, the new account has to
+        // accept us first
+        if followed_back
+            && self
+                .storage
+                .follow_by_ids(local.id, to.id)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to find follow back"))?
+                .is_none()
+        {
+            Follow::send(&local, &to, data).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn announce_blog_post(
+        &self,
+        url: Url,
+        title: String,
+        data: &Data<FederationData>,
+    ) -> Result<Note, ActivityError> {
+        let local = self.storage.get_local_account().await?;
+        let blog = blog_for_url(data, &url).await?;
+
+        let note_uri = generate_note_id(data)?;
+        let json = apub::Note::announcement(note_uri.clone(), &local, &url, &title);
+        let note = self
+            .storage
+            .new_post(
+                local.id,
+                note_uri.into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::from(&json),
+                ModerationState::Approved,
+            )
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to create announcement"))?;
+
+        CreateNote::send(&local, json, data).await?;
+
+        Ok(note)
+    }
+
+    async fn block_domain(
+        &self,
+        domain: &str,
+        reason: Option<&str>,
+        purge: bool,
+    ) -> Result<DomainBlock, ActivityError> {
+        let domain = normalize_domain(domain);
+        let local = self.storage.get_local_account().await?;
+        if domain.is_empty() || is_subdomain(local.uri.host_str().unwrap_or_default(), &domain) {
+            return Err(ActivityError::invalid_data(format!(
+                "Refusing to block domain '{}'",
+                domain
+            )));
+        }
+
+        let block = self
+            .storage
+            .new_domain_block(&domain, reason)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to block domain"))?;
+
+        if purge {
+            let accounts = self.storage.accounts_by_domain(&domain).await?;
+            info!("Purging {} accounts from {}", accounts.len(), domain);
+            for account in accounts {
+                self.storage
+                    .purge_account(account.id)
+                    .await
+                    .map_err(|e| ActivityError::storage(e, "Failed to purge account"))?;
+            }
+        }
+
+        Ok(block)
+    }
+
+    async fn unblock_domain(&self, domain: &str) -> Result<(), ActivityError> {
+        self.storage
+            .delete_domain_block(&normalize_domain(domain))
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to unblock domain"))
+    }
+
+    async fn moderate_note(
+        &self,
+        note_uri: Uri,
+        state: ModerationState,
+    ) -> Result<Note, ActivityError> {
+        let note = self
+            .storage
+            .post_by_uri(&note_uri)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to look up note"))?
+            .ok_or_else(|| ActivityError::invalid_data(format!("Unknown note {}", note_uri)))?;
+
+        info!("Moderating note {} as {:?}", note.uri, state);
+        self.storage
+            .set_moderation_state(note.id, state)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to moderate note"))?;
+
+        Ok(Note {
+            moderation_state: state,
+            ..note
+        })
+    }
+
+    async fn set_moderation_policy(
+        &self,
+        blog_url: &Url,
+        policy: ModerationPolicy,
+    ) -> Result<Blog, ActivityError> {
+        // The policy can be set up before the first comment arrives
+        let blog = match self
+            .storage
+            .blog_by_url(blog_url)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to look up blog"))?
+        {
+            Some(blog) => blog,
+            None => self
+                .storage
+                .new_blog(blog_url)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to create new blog"))?,
+        };
+
+        self.storage
+            .set_moderation_policy(blog.id, policy)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to set moderation policy"))?;
+
+        Ok(Blog {
+            moderation_policy: policy,
+            ..blog
+        })
+    }
+
+    async fn handle_report(
+        &self,
+        account: &Account,
+        report_uri: Uri,
+        content: Option<String>,
+        objects: Vec<Url>,
+    ) -> Result<(), ActivityError> {
+        info!("Received report {} from {}", report_uri, account.uri);
+        self.storage
+            .new_report(account.id, &report_uri, content.as_deref(), &objects)
+            .await
+            .map_err(|e| ActivityError::storage(e, "Failed to store report"))?;
+        Ok(())
+    }
+
+    async fn report_notes(
+        &self,
+        note_uris: Vec<Uri>,
+        comment: Option<String>,
+        data: &Data<FederationData>,
+    ) -> Result<usize, ActivityError> {
+        // Look up all the notes first, so that nothing is sent if any of them is unknown
+        let mut by_author: Vec<(AccountId, Vec<Url>)> = Vec::new();
+        for note_uri in note_uris {
+            let note = self
+                .storage
+                .post_by_uri(&note_uri)
+                .await
+                .map_err(|e| ActivityError::storage(e, "Failed to look up note"))?
+                .ok_or_else(|| ActivityError::invalid_data(format!("Unknown note {}", note_uri)))?;
+            match by_author.iter_mut().find(|(id, _)| *id == note.account_id) {
+                Some((_, notes)) => notes.push(note.uri.into()),
+                None => by_author.push((note.account_id, vec![note.uri.into()])),
+            }
+        }
+
+        let mut authors = Vec::with_capacity(by_author.len());
+        for (account_id, notes) in by_author {
+            let author = self
+                .storage
+                .account_by_id(account_id)
+                .await?
+                .ok_or(AccountError::NotFound)?;
+            if author.local {
+                return Err(ActivityError::invalid_data(
+                    "Notes of our own account cannot be reported",
+                ));
+            }
+            authors.push((author, notes));
+        }
+
+        let local = self.storage.get_local_account().await?;
+        for (author, notes) in &authors {
+            info!(
+                "Reporting {} notes of {} to its instance",
+                notes.len(),
+                author.uri
+            );
+            Flag::send(&local, author, notes.clone(), comment.clone(), data).await?;
+        }
+        Ok(authors.len())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::apub::Follow;
-    use crate::storage::AccountStorage;
+    use crate::storage::{AccountStorage, BlockStorage, BlogStorage, NoteStorage};
     use crate::testing;
     use crate::FederationData;
     use activitypub_federation::config::FederationConfig;
@@ -194,6 +510,7 @@ mod tests {
         let data = FederationData {
             config: crate::Config::load().unwrap(),
             service: Arc::new(service),
+            filters: Arc::default(),
         };
         let data = FederationConfig::builder()
             .app_data(data)
@@ -238,4 +555,127 @@ mod tests {
         assert_eq!(reverse_follow.target_account_id, actor_account.id);
         assert!(reverse_follow.pending);
     }
+
+    #[tokio::test]
+    async fn test_block_domain() {
+        let storage = testing::MemoryStorage::new("example.com");
+        let spammer = storage
+            .new_account(&testing::create_person("spammer", "spam.example.net"))
+            .await
+            .unwrap();
+        let other = storage
+            .new_account(&testing::create_person("other", "other.example.net"))
+            .await
+            .unwrap();
+        let service = Service::new(storage);
+
+        // Our own domain can't be blocked
+        let result = service.block_domain("example.com", None, true).await;
+        assert!(matches!(result, Err(ActivityError::InvalidData { .. })));
+
+        let block = service
+            .block_domain("Spam.Example.NET", Some("spam"), true)
+            .await
+            .unwrap();
+        assert_eq!(block.domain, "spam.example.net");
+
+        let storage = service.storage();
+        assert!(storage.account_by_id(spammer.id).await.unwrap().is_none());
+        assert!(storage.account_by_id(other.id).await.unwrap().is_some());
+        for (url, blocked) in [
+            ("https://spam.example.net/users/spammer", true),
+            ("https://sub.spam.example.net/notes/1", true),
+            ("https://notspam.example.net/users/spammer", false),
+            ("https://other.example.net/users/other", false),
+        ] {
+            assert_eq!(
+                storage.is_blocked(&Url::parse(url).unwrap()).await.unwrap(),
+                blocked,
+                "{}",
+                url
+            );
+        }
+
+        service.unblock_domain("spam.example.net").await.unwrap();
+        let url = Url::parse("https://spam.example.net/users/spammer").unwrap();
+        assert!(!storage.is_blocked(&url).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_moderation() {
+        let service = Service::new(testing::MemoryStorage::new("example.com"));
+
+        // The policy of a blog nobody has commented on yet can be set up front
+        let url = Url::parse("https://example.com/blog-post").unwrap();
+        let blog = service
+            .set_moderation_policy(&url, ModerationPolicy::HoldAll)
+            .await
+            .unwrap();
+        assert_eq!(blog.moderation_policy, ModerationPolicy::HoldAll);
+        let stored = service.storage().blog_by_url(&url).await.unwrap().unwrap();
+        assert_eq!(stored.id, blog.id);
+        assert_eq!(stored.moderation_policy, ModerationPolicy::HoldAll);
+
+        let result = service
+            .moderate_note(
+                Url::parse("https://example.com/notes/unknown")
+                    .unwrap()
+                    .into(),
+                ModerationState::Hidden,
+            )
+            .await;
+        assert!(matches!(result, Err(ActivityError::InvalidData { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_report_notes() {
+        let storage = testing::MemoryStorage::new("example.com");
+        let local = storage.get_local_account().await.unwrap();
+        let blog = storage
+            .new_blog(&Url::parse("https://example.com/blog").unwrap())
+            .await
+            .unwrap();
+        let own_note: Uri = Url::parse("https://example.com/notes/1").unwrap().into();
+        storage
+            .new_post(
+                local.id,
+                own_note.clone(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
+            .await
+            .unwrap();
+        let reporter = storage
+            .new_account(&testing::create_person("reporter", "remote.example"))
+            .await
+            .unwrap();
+        let federation = testing::federation_config(storage).await;
+        let data = federation.to_request_data();
+
+        let unknown: Uri = Url::parse("https://remote.example/notes/1").unwrap().into();
+        let result = data.service.report_notes(vec![unknown], None, &data).await;
+        assert!(matches!(result, Err(ActivityError::InvalidData { .. })));
+
+        let result = data.service.report_notes(vec![own_note], None, &data).await;
+        assert!(matches!(result, Err(ActivityError::InvalidData { .. })));
+
+        // Reports received from other instances are kept for review
+        let report_uri: Uri = Url::parse("https://remote.example/flag/1").unwrap().into();
+        data.service
+            .handle_report(
+                &reporter,
+                report_uri.clone(),
+                Some("Spam".to_string()),
+                vec![local.uri.clone().into()],
+            )
+            .await
+            .unwrap();
+        let reports = data.service.storage().unresolved_reports().await.unwrap();
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].uri, report_uri);
+        assert_eq!(reports[0].account_id, reporter.id);
+    }
 }
@@ -6,14 +6,20 @@
 use crate::{
     config::Database,
     storage::{
-        Account, AccountError, AccountId, AccountStorage, Blog, BlogError, BlogId, BlogStorage,
-        Follow, FollowError, FollowId, FollowStorage, Note, NoteError, NoteId, NoteStorage,
-        Storage,
+        Account, AccountError, AccountId, AccountStorage, ActorBlock, BlockError, BlockStorage,
+        Blog, BlogError, BlogId, BlogStorage, DomainBlock, Follow, FollowDirection, FollowError,
+        FollowId, FollowStorage, Job, JobError, JobId, JobKind, JobStorage, Like, LikeError,
+        LikeStorage, ModerationPolicy, ModerationState, Note, NoteContent, NoteError, NoteId,
+        NoteStorage, OutboxActivity, OutboxError, OutboxStorage, Report, ReportError,
+        ReportStorage, Repost, RepostError, RepostStorage, Storage, Thread, ThreadOrder,
+        ThreadPage,
     },
 };
 
 use crate::db::Uri;
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
 use thiserror::Error;
 use tracing::error;
 use url::Url;
@@ -37,6 +43,11 @@ impl SqliteStorage {
             .map_err(SqlError::Sqlx)?;
         Ok(Self { db })
     }
+
+    /// Uses an already open connection pool
+    pub fn with_pool(db: sqlx::SqlitePool) -> Self {
+        Self { db }
+    }
 }
 
 impl Storage for SqliteStorage {}
@@ -58,7 +69,13 @@ impl AccountStorage for SqliteStorage {
                 shared_inbox AS "shared_inbox: _",
                 public_key,
                 private_key,
-                local
+                local,
+                display_name,
+                avatar_url AS "avatar_url: _",
+                url AS "url: _",
+                bot,
+                published,
+                moved_to_id AS "moved_to_id: _"
             FROM accounts
             WHERE id = ?"#,
             id
@@ -83,7 +100,13 @@ impl AccountStorage for SqliteStorage {
                 shared_inbox AS "shared_inbox: _",
                 public_key,
                 private_key,
-                local
+                local,
+                display_name,
+                avatar_url AS "avatar_url: _",
+                url AS "url: _",
+                bot,
+                published,
+                moved_to_id AS "moved_to_id: _"
             FROM accounts
             WHERE uri = ?"#,
             uri
@@ -93,6 +116,76 @@ impl AccountStorage for SqliteStorage {
         .map_err(AccountError::SqlError)
     }
 
+    async fn accounts_updated_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Account>, AccountError> {
+        sqlx::query_as!(
+            Account,
+            r#"SELECT
+                id AS "id: _",
+                uri AS "uri: _",
+                created_at,
+                updated_at,
+                username,
+                host,
+                inbox AS "inbox: _",
+                outbox AS "outbox: _",
+                shared_inbox AS "shared_inbox: _",
+                public_key,
+                private_key,
+                local,
+                display_name,
+                avatar_url AS "avatar_url: _",
+                url AS "url: _",
+                bot,
+                published,
+                moved_to_id AS "moved_to_id: _"
+            FROM accounts
+            WHERE local = FALSE AND updated_at < ?
+            ORDER BY updated_at"#,
+            before
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(AccountError::SqlError)
+    }
+
+    async fn accounts_by_domain(&self, domain: &str) -> Result<Vec<Account>, AccountError> {
+        sqlx::query_as!(
+            Account,
+            r#"SELECT
+                id AS "id: _",
+                uri AS "uri: _",
+                created_at,
+                updated_at,
+                username,
+                host,
+                inbox AS "inbox: _",
+                outbox AS "outbox: _",
+                shared_inbox AS "shared_inbox: _",
+                public_key,
+                private_key,
+                local,
+                display_name,
+                avatar_url AS "avatar_url: _",
+                url AS "url: _",
+                bot,
+                published,
+                moved_to_id AS "moved_to_id: _"
+            FROM accounts
+            WHERE local = FALSE
+                AND (host = ? OR substr(host, -length(?) - 1) = '.' || ?)
+            ORDER BY id"#,
+            domain,
+            domain,
+            domain
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(AccountError::SqlError)
+    }
+
     async fn new_account(&self, person: &apub::Person) -> Result<Account, AccountError> {
         let uri: Uri = person.id.inner().clone().into();
         let host = person
@@ -103,7 +196,11 @@ impl AccountStorage for SqliteStorage {
             ))?;
         let inbox: Uri = person.inbox.clone().into();
         let outbox: Option<Uri> = person.outbox.clone().map(Into::into);
-        let shared_inbox: Option<Uri> = person.shared_inbox.clone().map(Into::into);
+        let shared_inbox: Option<Uri> = person.shared_inbox().cloned().map(Into::into);
+        let avatar_url: Option<Uri> = person.icon.as_ref().map(|icon| icon.url.clone().into());
+        let url: Option<Uri> = person.url.clone().map(Into::into);
+        let bot = person.is_bot();
+        let published = person.published.map(|published| published.naive_utc());
         let id = sqlx::query_scalar!(
             r#"INSERT INTO accounts (
                 uri,
@@ -113,9 +210,14 @@ impl AccountStorage for SqliteStorage {
                 outbox,
                 shared_inbox,
                 public_key,
-                local
+                local,
+                display_name,
+                avatar_url,
+                url,
+                bot,
+                published
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING id
             "#,
             uri,
@@ -125,7 +227,12 @@ impl AccountStorage for SqliteStorage {
             outbox,
             shared_inbox,
             person.public_key.public_key_pem,
-            false
+            false,
+            person.name,
+            avatar_url,
+            url,
+            bot,
+            published
         )
         .fetch_one(&self.db)
         .await
@@ -162,7 +269,11 @@ impl AccountStorage for SqliteStorage {
             ))?;
         let inbox: Uri = person.inbox.clone().into();
         let outbox: Option<Uri> = person.outbox.clone().map(Into::into);
-        let shared_inbox: Option<Uri> = person.shared_inbox.clone().map(Into::into);
+        let shared_inbox: Option<Uri> = person.shared_inbox().cloned().map(Into::into);
+        let avatar_url: Option<Uri> = person.icon.as_ref().map(|icon| icon.url.clone().into());
+        let url: Option<Uri> = person.url.clone().map(Into::into);
+        let bot = person.is_bot();
+        let published = person.published.map(|published| published.naive_utc());
         let id = sqlx::query_scalar!(
             r#"INSERT INTO accounts (
                 uri,
@@ -172,15 +283,26 @@ impl AccountStorage for SqliteStorage {
                 outbox,
                 shared_inbox,
                 public_key,
-                local
+                local,
+                display_name,
+                avatar_url,
+                url,
+                bot,
+                published
             )
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT DO UPDATE SET
+                username = excluded.username,
                 inbox = excluded.inbox,
                 outbox = excluded.outbox,
                 shared_inbox = excluded.shared_inbox,
                 public_key = excluded.public_key,
                 private_key = excluded.private_key,
+                display_name = excluded.display_name,
+                avatar_url = excluded.avatar_url,
+                url = excluded.url,
+                bot = excluded.bot,
+                published = excluded.published,
                 updated_at = DATETIME('now')
             RETURNING id
             "#,
@@ -191,7 +313,12 @@ impl AccountStorage for SqliteStorage {
             outbox,
             shared_inbox,
             person.public_key.public_key_pem,
-            false
+            false,
+            person.name,
+            avatar_url,
+            url,
+            bot,
+            published
         )
         .fetch_one(&self.db)
         .await
@@ -231,6 +358,121 @@ impl AccountStorage for SqliteStorage {
         Ok(())
     }
 
+    async fn purge_account(&self, id: AccountId) -> Result<(), AccountError> {
+        let mut tx = self.db.begin().await.map_err(AccountError::SqlError)?;
+        // Replies of other accounts are re-attached to their nearest ancestor that stays,
+        // otherwise the cascade would take them down together with the account's notes
+        sqlx::query!(
+            r#"WITH RECURSIVE ancestors(id, parent) AS (
+                SELECT id, reply_to_id FROM notes WHERE account_id = ?
+                UNION ALL
+                SELECT ancestors.id, notes.reply_to_id FROM ancestors
+                JOIN notes ON notes.id = ancestors.parent
+                WHERE notes.account_id = ?
+            )
+            UPDATE notes SET reply_to_id = (
+                SELECT parent FROM ancestors
+                WHERE ancestors.id = notes.reply_to_id
+                    AND (parent IS NULL
+                        OR parent NOT IN (SELECT id FROM notes WHERE account_id = ?))
+            )
+            WHERE account_id != ?
+                AND reply_to_id IN (SELECT id FROM notes WHERE account_id = ?)"#,
+            id,
+            id,
+            id,
+            id,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        // A thread whose root is gone falls apart into threads rooted at its top-most replies
+        sqlx::query!(
+            r#"WITH RECURSIVE threads(id, root) AS (
+                SELECT id, id FROM notes
+                WHERE account_id != ? AND reply_to_id IS NULL
+                    AND root_id IN (SELECT id FROM notes WHERE account_id = ?)
+                UNION ALL
+                SELECT notes.id, threads.root FROM notes
+                JOIN threads ON notes.reply_to_id = threads.id
+            )
+            UPDATE notes SET root_id = NULLIF(
+                (SELECT root FROM threads WHERE threads.id = notes.id),
+                notes.id
+            )
+            WHERE id IN (SELECT id FROM threads)"#,
+            id,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE notes SET likes = (
+                SELECT COUNT(*) FROM likes WHERE note_id = notes.id AND account_id != ?
+            )
+            WHERE id IN (SELECT note_id FROM likes WHERE account_id = ?)"#,
+            id,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE notes SET reposts = (
+                SELECT COUNT(*) FROM reposts WHERE note_id = notes.id AND account_id != ?
+            )
+            WHERE id IN (SELECT note_id FROM reposts WHERE account_id = ?)"#,
+            id,
+            id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        // Follows, the account's notes, likes and reposts are removed by the foreign key cascades
+        sqlx::query!(r#"DELETE FROM accounts WHERE id = ?"#, id)
+            .execute(&mut *tx)
+            .await
+            .map_err(AccountError::SqlError)?;
+        tx.commit().await.map_err(AccountError::SqlError)?;
+        Ok(())
+    }
+
+    async fn move_account(&self, from: AccountId, to: AccountId) -> Result<(), AccountError> {
+        let mut tx = self.db.begin().await.map_err(AccountError::SqlError)?;
+        // Whoever the old account followed is now followed by the new one, unless the new
+        // account already follows them
+        sqlx::query!(
+            r#"UPDATE OR IGNORE follows SET account_id = ? WHERE account_id = ?"#,
+            to,
+            from
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        // Drops the follows that were not transferred, and also the follows of the old account,
+        // those must be re-established by following the new account
+        sqlx::query!(
+            r#"DELETE FROM follows WHERE account_id = ? OR target_account_id = ?"#,
+            from,
+            from
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE accounts SET moved_to_id = ?, updated_at = DATETIME('now') WHERE id = ?"#,
+            to,
+            from
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(AccountError::SqlError)?;
+        tx.commit().await.map_err(AccountError::SqlError)?;
+        Ok(())
+    }
+
     async fn get_local_account(&self) -> Result<Account, AccountError> {
         sqlx::query_as!(
             Account,
@@ -246,7 +488,13 @@ impl AccountStorage for SqliteStorage {
                 shared_inbox AS "shared_inbox: _",
                 public_key,
                 private_key,
-                local
+                local,
+                display_name,
+                avatar_url AS "avatar_url: _",
+                url AS "url: _",
+                bot,
+                published,
+                moved_to_id AS "moved_to_id: _"
             FROM accounts
             WHERE local = 1
             "#,
@@ -393,6 +641,84 @@ impl FollowStorage for SqliteStorage {
             .map_err(FollowError::SqlError)?;
         Ok(())
     }
+
+    async fn follow_count(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+    ) -> Result<usize, FollowError> {
+        let count = match direction {
+            FollowDirection::Followers => sqlx::query_scalar!(
+                r#"SELECT COUNT(*) FROM follows WHERE target_account_id = ? AND pending = FALSE"#,
+                account_id
+            )
+            .fetch_one(&self.db)
+            .await
+            .map_err(FollowError::SqlError)?,
+            FollowDirection::Following => sqlx::query_scalar!(
+                r#"SELECT COUNT(*) FROM follows WHERE account_id = ? AND pending = FALSE"#,
+                account_id
+            )
+            .fetch_one(&self.db)
+            .await
+            .map_err(FollowError::SqlError)?,
+        };
+        Ok(count as usize)
+    }
+
+    async fn follow_account_uris(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uri>, FollowError> {
+        let offset = offset as i64;
+        let limit = limit as i64;
+        match direction {
+            FollowDirection::Followers => sqlx::query_scalar!(
+                r#"SELECT a.uri AS "uri: Uri"
+                FROM follows f
+                JOIN accounts a ON a.id = f.account_id
+                WHERE f.target_account_id = ? AND f.pending = FALSE
+                ORDER BY f.id DESC
+                LIMIT ? OFFSET ?"#,
+                account_id,
+                limit,
+                offset
+            )
+            .fetch_all(&self.db)
+            .await
+            .map_err(FollowError::SqlError),
+            FollowDirection::Following => sqlx::query_scalar!(
+                r#"SELECT a.uri AS "uri: Uri"
+                FROM follows f
+                JOIN accounts a ON a.id = f.target_account_id
+                WHERE f.account_id = ? AND f.pending = FALSE
+                ORDER BY f.id DESC
+                LIMIT ? OFFSET ?"#,
+                account_id,
+                limit,
+                offset
+            )
+            .fetch_all(&self.db)
+            .await
+            .map_err(FollowError::SqlError),
+        }
+    }
+
+    async fn follower_inboxes(&self, account_id: AccountId) -> Result<Vec<Uri>, FollowError> {
+        sqlx::query_scalar!(
+            r#"SELECT DISTINCT COALESCE(a.shared_inbox, a.inbox) AS "inbox!: Uri"
+            FROM follows f
+            JOIN accounts a ON a.id = f.account_id
+            WHERE f.target_account_id = ? AND f.pending = FALSE"#,
+            account_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(FollowError::SqlError)
+    }
 }
 
 #[async_trait]
@@ -427,7 +753,8 @@ impl BlogStorage for SqliteStorage {
             r#"SELECT
                 id,
                 created_at,
-                url
+                url,
+                moderation_policy AS "moderation_policy: ModerationPolicy"
             FROM blogs
             WHERE id = ?"#,
             id
@@ -444,6 +771,7 @@ impl BlogStorage for SqliteStorage {
                     error!("Failed to parse blog URL: {}", e);
                     BlogError::UrlParseError(e)
                 })?,
+                moderation_policy: record.moderation_policy,
             })),
             None => Ok(None),
         }
@@ -455,7 +783,8 @@ impl BlogStorage for SqliteStorage {
             r#"SELECT
                 id,
                 created_at,
-                url
+                url,
+                moderation_policy AS "moderation_policy: ModerationPolicy"
             FROM blogs
             WHERE url = ?"#,
             url
@@ -472,6 +801,7 @@ impl BlogStorage for SqliteStorage {
                     error!("Failed to parse blog URL: {}", e);
                     BlogError::UrlParseError(e)
                 })?,
+                moderation_policy: record.moderation_policy,
             })),
             None => Ok(None),
         }
@@ -484,6 +814,22 @@ impl BlogStorage for SqliteStorage {
             .map_err(BlogError::SqlError)?;
         Ok(())
     }
+
+    async fn set_moderation_policy(
+        &self,
+        id: BlogId,
+        policy: ModerationPolicy,
+    ) -> Result<(), BlogError> {
+        sqlx::query!(
+            r#"UPDATE blogs SET moderation_policy = ? WHERE id = ?"#,
+            policy,
+            id
+        )
+        .execute(&self.db)
+        .await
+        .map_err(BlogError::SqlError)?;
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -495,23 +841,40 @@ impl NoteStorage for SqliteStorage {
         reply_to_id: Option<NoteId>,
         root_id: Option<NoteId>,
         blog_id: BlogId,
+        content: NoteContent,
+        moderation_state: ModerationState,
     ) -> Result<Note, NoteError> {
+        let tags = Json(content.tags);
         let id = sqlx::query_scalar!(
             r#"INSERT INTO notes (
                 account_id,
                 uri,
                 reply_to_id,
                 root_id,
-                blog_id
+                blog_id,
+                content,
+                summary,
+                published,
+                language,
+                url,
+                tags,
+                moderation_state
             )
-            VALUES (?, ?, ?, ?, ?)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             RETURNING id
             "#,
             account_id,
             uri,
             reply_to_id,
             root_id,
-            blog_id
+            blog_id,
+            content.content,
+            content.summary,
+            content.published,
+            content.language,
+            content.url,
+            tags,
+            moderation_state
         )
         .fetch_one(&self.db)
         .await
@@ -548,7 +911,15 @@ impl NoteStorage for SqliteStorage {
                 root_id AS "root_id: _",
                 blog_id AS "blog_id: _",
                 likes,
-                reposts
+                reposts,
+                content,
+                summary,
+                published AS "published: _",
+                language,
+                url AS "url: _",
+                tags AS "tags: _",
+                deleted_at,
+                moderation_state AS "moderation_state: _"
             FROM notes
             WHERE id = ?"#,
             id
@@ -571,7 +942,15 @@ impl NoteStorage for SqliteStorage {
                 root_id AS "root_id: _",
                 blog_id AS "blog_id: _",
                 likes,
-                reposts
+                reposts,
+                content,
+                summary,
+                published AS "published: _",
+                language,
+                url AS "url: _",
+                tags AS "tags: _",
+                deleted_at,
+                moderation_state AS "moderation_state: _"
             FROM notes
             WHERE uri = ?"#,
             uri
@@ -589,6 +968,141 @@ impl NoteStorage for SqliteStorage {
         Ok(())
     }
 
+    async fn update_post(&self, id: NoteId, content: NoteContent) -> Result<Note, NoteError> {
+        let tags = Json(content.tags);
+        sqlx::query!(
+            r#"UPDATE notes SET
+                content = ?,
+                summary = ?,
+                published = ?,
+                language = ?,
+                url = ?,
+                tags = ?,
+                updated_at = DATETIME('now')
+            WHERE id = ?"#,
+            content.content,
+            content.summary,
+            content.published,
+            content.language,
+            content.url,
+            tags,
+            id
+        )
+        .execute(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+
+        self.post_by_id(id).await?.ok_or(NoteError::NotFound)
+    }
+
+    async fn tombstone_post(&self, id: NoteId) -> Result<(), NoteError> {
+        sqlx::query!(
+            r#"UPDATE notes SET
+                content = '',
+                summary = NULL,
+                language = NULL,
+                tags = '[]',
+                updated_at = DATETIME('now'),
+                deleted_at = DATETIME('now')
+            WHERE id = ?"#,
+            id
+        )
+        .execute(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+        Ok(())
+    }
+
+    async fn move_thread_to_blog(&self, root_id: NoteId, blog_id: BlogId) -> Result<(), NoteError> {
+        sqlx::query!(
+            r#"UPDATE notes SET blog_id = ? WHERE id = ? OR root_id = ?"#,
+            blog_id,
+            root_id,
+            root_id
+        )
+        .execute(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+        Ok(())
+    }
+
+    async fn thread_by_blog_id(
+        &self,
+        blog_id: BlogId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError> {
+        let notes = sqlx::query_as!(
+            Note,
+            r#"SELECT
+                id AS "id: _",
+                created_at AS "created_at: _",
+                updated_at AS "updated_at: _",
+                account_id AS "account_id: _",
+                uri AS "uri: _",
+                reply_to_id AS "reply_to_id: _",
+                root_id AS "root_id: _",
+                blog_id AS "blog_id: _",
+                likes,
+                reposts,
+                content,
+                summary,
+                published AS "published: _",
+                language,
+                url AS "url: _",
+                tags AS "tags: _",
+                deleted_at,
+                moderation_state AS "moderation_state: _"
+            FROM notes
+            WHERE blog_id = ? AND moderation_state = 'approved'"#,
+            blog_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+
+        Ok(Thread::build(notes, order, page))
+    }
+
+    async fn thread_by_root_id(
+        &self,
+        root_id: NoteId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError> {
+        let notes = sqlx::query_as!(
+            Note,
+            r#"SELECT
+                id AS "id: _",
+                created_at AS "created_at: _",
+                updated_at AS "updated_at: _",
+                account_id AS "account_id: _",
+                uri AS "uri: _",
+                reply_to_id AS "reply_to_id: _",
+                root_id AS "root_id: _",
+                blog_id AS "blog_id: _",
+                likes,
+                reposts,
+                content,
+                summary,
+                published AS "published: _",
+                language,
+                url AS "url: _",
+                tags AS "tags: _",
+                deleted_at,
+                moderation_state AS "moderation_state: _"
+            FROM notes
+            WHERE (id = ? OR root_id = ?) AND moderation_state = 'approved'"#,
+            root_id,
+            root_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+
+        Ok(Thread::build(notes, order, page))
+    }
+
     async fn post_count(&self) -> Result<usize, NoteError> {
         let count = sqlx::query_scalar!(r#"SELECT COUNT(*) FROM notes"#)
             .fetch_one(&self.db)
@@ -597,10 +1111,15 @@ impl NoteStorage for SqliteStorage {
         Ok(count as usize)
     }
 
-    async fn like_post(&self, post_uri: &Uri) -> Result<(), NoteError> {
+    async fn set_moderation_state(
+        &self,
+        id: NoteId,
+        state: ModerationState,
+    ) -> Result<(), NoteError> {
         sqlx::query!(
-            r#"UPDATE notes SET likes = likes + 1 WHERE uri = ?"#,
-            post_uri
+            r#"UPDATE notes SET moderation_state = ? WHERE id = ?"#,
+            state,
+            id
         )
         .execute(&self.db)
         .await
@@ -608,14 +1127,595 @@ impl NoteStorage for SqliteStorage {
         Ok(())
     }
 
-    async fn unlike_post(&self, post_uri: &Uri) -> Result<(), NoteError> {
+    async fn posts_by_moderation_state(
+        &self,
+        state: ModerationState,
+    ) -> Result<Vec<Note>, NoteError> {
+        sqlx::query_as!(
+            Note,
+            r#"SELECT
+                id AS "id: _",
+                created_at AS "created_at: _",
+                updated_at AS "updated_at: _",
+                account_id AS "account_id: _",
+                uri AS "uri: _",
+                reply_to_id AS "reply_to_id: _",
+                root_id AS "root_id: _",
+                blog_id AS "blog_id: _",
+                likes,
+                reposts,
+                content,
+                summary,
+                published AS "published: _",
+                language,
+                url AS "url: _",
+                tags AS "tags: _",
+                deleted_at,
+                moderation_state AS "moderation_state: _"
+            FROM notes
+            WHERE moderation_state = ?
+            ORDER BY id"#,
+            state
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(NoteError::SqlError)
+    }
+
+    async fn has_approved_posts(&self, account_id: AccountId) -> Result<bool, NoteError> {
+        let approved = sqlx::query_scalar!(
+            r#"SELECT EXISTS(
+                SELECT 1 FROM notes WHERE account_id = ? AND moderation_state = 'approved'
+            ) AS "approved!: bool""#,
+            account_id
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(NoteError::SqlError)?;
+        Ok(approved)
+    }
+}
+
+#[async_trait]
+impl RepostStorage for SqliteStorage {
+    async fn new_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Repost, RepostError> {
+        let mut tx = self.db.begin().await.map_err(RepostError::SqlError)?;
+        sqlx::query!(
+            r#"INSERT INTO reposts (note_id, account_id, uri)
+            VALUES (?, ?, ?)
+            ON CONFLICT (note_id, account_id) DO NOTHING"#,
+            note_id,
+            account_id,
+            uri
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(RepostError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE notes SET reposts = (SELECT COUNT(*) FROM reposts WHERE note_id = ?) WHERE id = ?"#,
+            note_id,
+            note_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(RepostError::SqlError)?;
+        tx.commit().await.map_err(RepostError::SqlError)?;
+
+        self.repost_by_ids(note_id, account_id)
+            .await?
+            .ok_or(RepostError::NotFound)
+    }
+
+    async fn repost_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Repost>, RepostError> {
+        sqlx::query_as!(
+            Repost,
+            r#"SELECT
+                id,
+                created_at,
+                note_id,
+                account_id,
+                uri AS "uri: _"
+            FROM reposts
+            WHERE note_id = ? AND account_id = ?"#,
+            note_id,
+            account_id
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(RepostError::SqlError)
+    }
+
+    async fn reposts_by_note_id(&self, note_id: NoteId) -> Result<Vec<Repost>, RepostError> {
+        sqlx::query_as!(
+            Repost,
+            r#"SELECT
+                id,
+                created_at,
+                note_id,
+                account_id,
+                uri AS "uri: _"
+            FROM reposts
+            WHERE note_id = ?
+            ORDER BY id"#,
+            note_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(RepostError::SqlError)
+    }
+
+    async fn delete_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<(), RepostError> {
+        let mut tx = self.db.begin().await.map_err(RepostError::SqlError)?;
+        sqlx::query!(
+            r#"DELETE FROM reposts WHERE note_id = ? AND account_id = ?"#,
+            note_id,
+            account_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(RepostError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE notes SET reposts = (SELECT COUNT(*) FROM reposts WHERE note_id = ?) WHERE id = ?"#,
+            note_id,
+            note_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(RepostError::SqlError)?;
+        tx.commit().await.map_err(RepostError::SqlError)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LikeStorage for SqliteStorage {
+    async fn new_like(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Like, LikeError> {
+        let mut tx = self.db.begin().await.map_err(LikeError::SqlError)?;
+        sqlx::query!(
+            r#"INSERT INTO likes (note_id, account_id, uri)
+            VALUES (?, ?, ?)
+            ON CONFLICT (note_id, account_id) DO NOTHING"#,
+            note_id,
+            account_id,
+            uri
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(LikeError::SqlError)?;
         sqlx::query!(
-            r#"UPDATE notes SET likes = likes - 1 WHERE uri = ?"#,
-            post_uri
+            r#"UPDATE notes SET likes = (SELECT COUNT(*) FROM likes WHERE note_id = ?) WHERE id = ?"#,
+            note_id,
+            note_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(LikeError::SqlError)?;
+        tx.commit().await.map_err(LikeError::SqlError)?;
+
+        self.like_by_ids(note_id, account_id)
+            .await?
+            .ok_or(LikeError::NotFound)
+    }
+
+    async fn like_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Like>, LikeError> {
+        sqlx::query_as!(
+            Like,
+            r#"SELECT
+                id,
+                created_at,
+                note_id,
+                account_id,
+                uri AS "uri: _"
+            FROM likes
+            WHERE note_id = ? AND account_id = ?"#,
+            note_id,
+            account_id
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(LikeError::SqlError)
+    }
+
+    async fn likes_by_note_id(&self, note_id: NoteId) -> Result<Vec<Like>, LikeError> {
+        sqlx::query_as!(
+            Like,
+            r#"SELECT
+                id,
+                created_at,
+                note_id,
+                account_id,
+                uri AS "uri: _"
+            FROM likes
+            WHERE note_id = ?
+            ORDER BY id"#,
+            note_id
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(LikeError::SqlError)
+    }
+
+    async fn delete_like(&self, note_id: NoteId, account_id: AccountId) -> Result<(), LikeError> {
+        let mut tx = self.db.begin().await.map_err(LikeError::SqlError)?;
+        sqlx::query!(
+            r#"DELETE FROM likes WHERE note_id = ? AND account_id = ?"#,
+            note_id,
+            account_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(LikeError::SqlError)?;
+        sqlx::query!(
+            r#"UPDATE notes SET likes = (SELECT COUNT(*) FROM likes WHERE note_id = ?) WHERE id = ?"#,
+            note_id,
+            note_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(LikeError::SqlError)?;
+        tx.commit().await.map_err(LikeError::SqlError)?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for SqliteStorage {
+    async fn new_outbox_activity(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        activity: serde_json::Value,
+        public: bool,
+    ) -> Result<OutboxActivity, OutboxError> {
+        let activity = Json(activity);
+        sqlx::query!(
+            r#"INSERT INTO outbox (account_id, uri, activity, public)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (uri) DO NOTHING"#,
+            account_id,
+            uri,
+            activity,
+            public
         )
         .execute(&self.db)
         .await
-        .map_err(NoteError::SqlError)?;
+        .map_err(OutboxError::SqlError)?;
+
+        sqlx::query_as!(
+            OutboxActivity,
+            r#"SELECT
+                id,
+                created_at,
+                account_id,
+                uri AS "uri: _",
+                activity AS "activity: _",
+                public
+            FROM outbox
+            WHERE uri = ?"#,
+            uri
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(OutboxError::SqlError)?
+        .ok_or(OutboxError::NotFound)
+    }
+
+    async fn outbox_count(&self, account_id: AccountId) -> Result<usize, OutboxError> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) FROM outbox WHERE account_id = ? AND public = TRUE"#,
+            account_id
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(OutboxError::SqlError)?;
+        Ok(count as usize)
+    }
+
+    async fn outbox_activities(
+        &self,
+        account_id: AccountId,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<OutboxActivity>, OutboxError> {
+        let offset = offset as i64;
+        let limit = limit as i64;
+        sqlx::query_as!(
+            OutboxActivity,
+            r#"SELECT
+                id,
+                created_at,
+                account_id,
+                uri AS "uri: _",
+                activity AS "activity: _",
+                public
+            FROM outbox
+            WHERE account_id = ? AND public = TRUE
+            ORDER BY id DESC
+            LIMIT ? OFFSET ?"#,
+            account_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(OutboxError::SqlError)
+    }
+}
+
+#[async_trait]
+impl JobStorage for SqliteStorage {
+    async fn new_job(&self, kind: JobKind, target: &str) -> Result<Job, JobError> {
+        sqlx::query_as!(
+            Job,
+            r#"INSERT INTO jobs (kind, target)
+            VALUES (?, ?)
+            RETURNING
+                id,
+                created_at,
+                kind AS "kind: _",
+                target,
+                started_at,
+                finished_at,
+                error"#,
+            kind,
+            target
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(JobError::SqlError)
+    }
+
+    async fn job_by_id(&self, id: JobId) -> Result<Option<Job>, JobError> {
+        sqlx::query_as!(
+            Job,
+            r#"SELECT
+                id,
+                created_at,
+                kind AS "kind: _",
+                target,
+                started_at,
+                finished_at,
+                error
+            FROM jobs
+            WHERE id = ?"#,
+            id
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(JobError::SqlError)
+    }
+
+    async fn start_next_job(&self) -> Result<Option<Job>, JobError> {
+        sqlx::query_as!(
+            Job,
+            r#"UPDATE jobs SET started_at = DATETIME('now')
+            WHERE id = (SELECT id FROM jobs WHERE started_at IS NULL ORDER BY id LIMIT 1)
+            RETURNING
+                id,
+                created_at,
+                kind AS "kind: _",
+                target,
+                started_at,
+                finished_at,
+                error"#
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(JobError::SqlError)
+    }
+
+    async fn finish_job(&self, id: JobId, error: Option<&str>) -> Result<(), JobError> {
+        sqlx::query!(
+            r#"UPDATE jobs SET finished_at = DATETIME('now'), error = ? WHERE id = ?"#,
+            error,
+            id
+        )
+        .execute(&self.db)
+        .await
+        .map_err(JobError::SqlError)?;
         Ok(())
     }
 }
+
+#[async_trait]
+impl ReportStorage for SqliteStorage {
+    async fn new_report(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        content: Option<&str>,
+        objects: &[Url],
+    ) -> Result<Report, ReportError> {
+        let objects = Json(objects);
+        sqlx::query!(
+            r#"INSERT INTO reports (account_id, uri, content, objects)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT (uri) DO NOTHING"#,
+            account_id,
+            uri,
+            content,
+            objects
+        )
+        .execute(&self.db)
+        .await
+        .map_err(ReportError::SqlError)?;
+
+        sqlx::query_as!(
+            Report,
+            r#"SELECT
+                id,
+                created_at,
+                account_id,
+                uri AS "uri: _",
+                content,
+                objects AS "objects: _",
+                resolved_at
+            FROM reports
+            WHERE uri = ?"#,
+            uri
+        )
+        .fetch_optional(&self.db)
+        .await
+        .map_err(ReportError::SqlError)?
+        .ok_or(ReportError::NotFound)
+    }
+
+    async fn unresolved_reports(&self) -> Result<Vec<Report>, ReportError> {
+        sqlx::query_as!(
+            Report,
+            r#"SELECT
+                id,
+                created_at,
+                account_id,
+                uri AS "uri: _",
+                content,
+                objects AS "objects: _",
+                resolved_at
+            FROM reports
+            WHERE resolved_at IS NULL
+            ORDER BY id"#
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(ReportError::SqlError)
+    }
+}
+
+#[async_trait]
+impl BlockStorage for SqliteStorage {
+    async fn new_domain_block(
+        &self,
+        domain: &str,
+        reason: Option<&str>,
+    ) -> Result<DomainBlock, BlockError> {
+        sqlx::query_as!(
+            DomainBlock,
+            r#"INSERT INTO domain_blocks (domain, reason)
+            VALUES (?, ?)
+            ON CONFLICT (domain) DO UPDATE SET reason = excluded.reason
+            RETURNING
+                id AS "id!: _",
+                created_at,
+                domain,
+                reason"#,
+            domain,
+            reason
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(BlockError::SqlError)
+    }
+
+    async fn delete_domain_block(&self, domain: &str) -> Result<(), BlockError> {
+        sqlx::query!(r#"DELETE FROM domain_blocks WHERE domain = ?"#, domain)
+            .execute(&self.db)
+            .await
+            .map_err(BlockError::SqlError)?;
+        Ok(())
+    }
+
+    async fn domain_blocks(&self) -> Result<Vec<DomainBlock>, BlockError> {
+        sqlx::query_as!(
+            DomainBlock,
+            r#"SELECT
+                id,
+                created_at,
+                domain,
+                reason
+            FROM domain_blocks
+            ORDER BY domain"#
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(BlockError::SqlError)
+    }
+
+    async fn new_actor_block(
+        &self,
+        uri: &Uri,
+        reason: Option<&str>,
+    ) -> Result<ActorBlock, BlockError> {
+        sqlx::query_as!(
+            ActorBlock,
+            r#"INSERT INTO actor_blocks (uri, reason)
+            VALUES (?, ?)
+            ON CONFLICT (uri) DO UPDATE SET reason = excluded.reason
+            RETURNING
+                id AS "id!: _",
+                created_at,
+                uri AS "uri: _",
+                reason"#,
+            uri,
+            reason
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(BlockError::SqlError)
+    }
+
+    async fn delete_actor_block(&self, uri: &Uri) -> Result<(), BlockError> {
+        sqlx::query!(r#"DELETE FROM actor_blocks WHERE uri = ?"#, uri)
+            .execute(&self.db)
+            .await
+            .map_err(BlockError::SqlError)?;
+        Ok(())
+    }
+
+    async fn actor_blocks(&self) -> Result<Vec<ActorBlock>, BlockError> {
+        sqlx::query_as!(
+            ActorBlock,
+            r#"SELECT
+                id,
+                created_at,
+                uri AS "uri: _",
+                reason
+            FROM actor_blocks
+            ORDER BY uri"#
+        )
+        .fetch_all(&self.db)
+        .await
+        .map_err(BlockError::SqlError)
+    }
+
+    async fn is_blocked(&self, url: &Url) -> Result<bool, BlockError> {
+        let uri = url.as_str();
+        let host = url.host_str().unwrap_or_default().to_lowercase();
+        let blocked = sqlx::query_scalar!(
+            r#"SELECT
+                EXISTS (SELECT 1 FROM actor_blocks WHERE uri = ?)
+                OR EXISTS (
+                    SELECT 1 FROM domain_blocks
+                    WHERE domain = ? OR substr(?, -length(domain) - 1) = '.' || domain
+                ) AS "blocked!: bool""#,
+            uri,
+            host,
+            host
+        )
+        .fetch_one(&self.db)
+        .await
+        .map_err(BlockError::SqlError)?;
+        Ok(blocked)
+    }
+}
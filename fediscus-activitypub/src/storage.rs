@@ -4,16 +4,45 @@
 // SPDX-License-Identifier: MIT
 
 mod account;
+mod block;
 mod blog;
 mod follow;
+mod job;
+mod like;
 mod note;
+mod outbox;
+mod report;
+mod repost;
 
 use async_trait::async_trait;
 
 pub use account::{Account, AccountError, AccountId, AccountStorage};
-pub use blog::{Blog, BlogError, BlogId, BlogStorage};
-pub use follow::{Follow, FollowError, FollowId, FollowStorage};
-pub use note::{Note, NoteError, NoteId, NoteStorage};
+pub use block::{
+    is_subdomain, normalize_domain, ActorBlock, BlockError, BlockId, BlockStorage, DomainBlock,
+};
+pub use blog::{Blog, BlogError, BlogId, BlogStorage, ModerationPolicy};
+pub use follow::{Follow, FollowDirection, FollowError, FollowId, FollowStorage};
+pub use job::{Job, JobError, JobId, JobKind, JobStorage};
+pub use like::{Like, LikeError, LikeId, LikeStorage};
+pub use note::{
+    ModerationState, Note, NoteContent, NoteError, NoteId, NoteStorage, Thread, ThreadNote,
+    ThreadOrder, ThreadPage,
+};
+pub use outbox::{OutboxActivity, OutboxActivityId, OutboxError, OutboxStorage};
+pub use report::{Report, ReportError, ReportId, ReportStorage};
+pub use repost::{Repost, RepostError, RepostId, RepostStorage};
 
 #[async_trait]
-pub trait Storage: AccountStorage + FollowStorage + BlogStorage + NoteStorage {}
+pub trait Storage:
+    AccountStorage
+    + FollowStorage
+    + BlogStorage
+    + NoteStorage
+    + LikeStorage
+    + RepostStorage
+    + OutboxStorage
+    + BlockStorage
+    + JobStorage
+    + ReportStorage
+{
+}
@@ -7,7 +7,7 @@ use activitypub_federation::config::Data;
 use activitypub_federation::protocol::public_key::PublicKey;
 use activitypub_federation::protocol::verification;
 use activitypub_federation::traits::Actor;
-use activitypub_federation::{kinds::actor::PersonType, traits::Object};
+use activitypub_federation::traits::Object;
 use async_trait::async_trait;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use thiserror::Error;
@@ -52,6 +52,36 @@ pub struct Account {
     pub public_key: String,
     pub private_key: Option<String>,
     pub local: bool,
+    /// The name to show instead of the username
+    pub display_name: Option<String>,
+    pub avatar_url: Option<Uri>,
+    /// Link to the profile page of the account on its instance
+    pub url: Option<Uri>,
+    /// Whether the account is automated
+    pub bot: bool,
+    /// When the account was created on its instance, if the instance tells us
+    pub published: Option<NaiveDateTime>,
+    /// The account this account has migrated to
+    pub moved_to_id: Option<AccountId>,
+}
+
+impl Account {
+    /// URL of the followers collection, only served for the local account
+    pub fn followers_url(&self) -> Option<Url> {
+        self.collection_url("followers")
+    }
+
+    /// URL of the following collection, only served for the local account
+    pub fn following_url(&self) -> Option<Url> {
+        self.collection_url("following")
+    }
+
+    fn collection_url(&self, name: &str) -> Option<Url> {
+        if !self.local {
+            return None;
+        }
+        Url::parse(&format!("{}/{}", self.uri, name)).ok()
+    }
 }
 
 #[async_trait]
@@ -75,14 +105,30 @@ impl Object for Account {
     }
 
     async fn into_json(self, _: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
-        let shared_inbox = self.shared_inbox().map(Into::into);
+        let endpoints = self.shared_inbox().map(|shared_inbox| apub::Endpoints {
+            shared_inbox: Some(shared_inbox),
+        });
         Ok(apub::Person {
-            kind: PersonType::Person,
+            kind: if self.bot {
+                apub::ActorType::Service
+            } else {
+                apub::ActorType::Person
+            },
             preferred_username: self.username.clone(),
+            name: self.display_name.clone(),
+            icon: self
+                .avatar_url
+                .clone()
+                .map(|url| apub::Image::new(url.into())),
+            url: self.url.clone().map(Into::into),
+            also_known_as: Vec::new(),
+            published: self.published.map(|published| published.and_utc()),
             id: self.uri.clone().into(),
             inbox: self.inbox(),
+            followers: self.followers_url(),
+            following: self.following_url(),
             outbox: self.outbox.map(Into::into),
-            shared_inbox,
+            endpoints,
             public_key: PublicKey {
                 id: format!("{}#main-key", self.uri),
                 owner: self.uri.into(),
@@ -103,6 +149,11 @@ impl Object for Account {
     fn last_refreshed_at(&self) -> Option<DateTime<Utc>> {
         Some(self.updated_at.and_utc())
     }
+
+    /// Called when the account has been found to be gone from its instance when refetching it
+    async fn delete(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        data.service.storage().purge_account(self.id).await
+    }
 }
 
 impl Actor for Account {
@@ -123,6 +174,9 @@ impl Actor for Account {
     }
 
     fn shared_inbox(&self) -> Option<Url> {
+        if self.local {
+            return self.uri.as_url().join("/inbox").ok();
+        }
         self.shared_inbox.clone().map(Into::into)
     }
 }
@@ -137,6 +191,16 @@ pub trait AccountStorage {
 
     async fn get_local_account(&self) -> Result<Account, AccountError>;
 
+    /// Returns all remote accounts that have not been updated since `before`, least recently
+    /// updated first
+    async fn accounts_updated_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Account>, AccountError>;
+
+    /// Returns all remote accounts hosted on the domain or any of its subdomains
+    async fn accounts_by_domain(&self, domain: &str) -> Result<Vec<Account>, AccountError>;
+
     async fn update_or_insert_account(
         &self,
         person: &apub::Person,
@@ -145,4 +209,13 @@ pub trait AccountStorage {
     async fn delete_account_by_id(&self, id: AccountId) -> Result<(), AccountError>;
 
     async fn delete_account_by_uri(&self, uri: &Uri) -> Result<(), AccountError>;
+
+    /// Removes the account together with all its notes, follows, likes and reposts, keeping
+    /// the like and repost counts of other notes in sync. Replies of other accounts to the
+    /// removed notes are re-attached to their nearest remaining ancestor.
+    async fn purge_account(&self, id: AccountId) -> Result<(), AccountError>;
+
+    /// Records that the account has migrated to another account and transfers the follows of
+    /// the old account to the new one. Follows of the old account are removed.
+    async fn move_account(&self, from: AccountId, to: AccountId) -> Result<(), AccountError>;
 }
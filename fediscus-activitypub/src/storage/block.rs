@@ -0,0 +1,76 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use thiserror::Error;
+use url::Url;
+
+use crate::db::Uri;
+
+pub use fediscus_common::domain::{is_subdomain, normalize_domain};
+
+#[derive(Debug, Error)]
+pub enum BlockError {
+    #[error("Block not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct BlockId(i64);
+
+impl From<i64> for BlockId {
+    fn from(id: i64) -> Self {
+        BlockId(id)
+    }
+}
+
+/// A blocked instance, the block applies to its subdomains as well
+#[derive(Debug, Clone)]
+pub struct DomainBlock {
+    pub id: BlockId,
+    pub created_at: NaiveDateTime,
+    pub domain: String,
+    pub reason: Option<String>,
+}
+
+/// A blocked actor
+#[derive(Debug, Clone)]
+pub struct ActorBlock {
+    pub id: BlockId,
+    pub created_at: NaiveDateTime,
+    pub uri: Uri,
+    pub reason: Option<String>,
+}
+
+#[async_trait]
+pub trait BlockStorage {
+    /// Blocks the domain, blocking an already blocked domain updates the reason
+    async fn new_domain_block(
+        &self,
+        domain: &str,
+        reason: Option<&str>,
+    ) -> Result<DomainBlock, BlockError>;
+
+    async fn delete_domain_block(&self, domain: &str) -> Result<(), BlockError>;
+
+    async fn domain_blocks(&self) -> Result<Vec<DomainBlock>, BlockError>;
+
+    /// Blocks the actor, blocking an already blocked actor updates the reason
+    async fn new_actor_block(
+        &self,
+        uri: &Uri,
+        reason: Option<&str>,
+    ) -> Result<ActorBlock, BlockError>;
+
+    async fn delete_actor_block(&self, uri: &Uri) -> Result<(), BlockError>;
+
+    async fn actor_blocks(&self) -> Result<Vec<ActorBlock>, BlockError>;
+
+    /// Returns whether the URL is a blocked actor or belongs to a blocked domain
+    async fn is_blocked(&self, url: &Url) -> Result<bool, BlockError>;
+}
@@ -30,11 +30,26 @@ impl From<i64> for BlogId {
     }
 }
 
+/// How replies in the threads about a blog post are moderated
+#[derive(sqlx::Type, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[sqlx(rename_all = "snake_case")]
+pub enum ModerationPolicy {
+    /// Replies are published right away
+    #[default]
+    AutoApprove,
+    /// Replies of accounts that already have an approved note are published right away,
+    /// others are held for moderation
+    ApproveKnown,
+    /// All replies are held for moderation
+    HoldAll,
+}
+
 #[derive(Debug, Clone)]
 pub struct Blog {
     pub id: BlogId,
     pub created_at: NaiveDateTime,
     pub url: Url,
+    pub moderation_policy: ModerationPolicy,
 }
 
 #[async_trait]
@@ -46,4 +61,10 @@ pub trait BlogStorage {
     async fn blog_by_url(&self, url: &Url) -> Result<Option<Blog>, BlogError>;
 
     async fn delete_blog_by_id(&self, id: BlogId) -> Result<(), BlogError>;
+
+    async fn set_moderation_policy(
+        &self,
+        id: BlogId,
+        policy: ModerationPolicy,
+    ) -> Result<(), BlogError>;
 }
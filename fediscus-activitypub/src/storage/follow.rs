@@ -48,6 +48,15 @@ pub struct Follow {
     pub pending: bool,
 }
 
+/// Which side of the follow relationships of an account to look at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FollowDirection {
+    /// Accounts following the account
+    Followers,
+    /// Accounts followed by the account
+    Following,
+}
+
 #[async_trait]
 pub trait FollowStorage {
     async fn new_follow(
@@ -76,6 +85,27 @@ pub trait FollowStorage {
     async fn delete_follow_by_id(&self, follow_id: FollowId) -> Result<(), FollowError>;
 
     async fn follow_accepted(&self, uri: &Uri) -> Result<(), FollowError>;
+
+    /// Returns the number of accepted follows of the account in the given direction
+    async fn follow_count(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+    ) -> Result<usize, FollowError>;
+
+    /// Returns the URIs of the accounts on the other side of the accepted follows of the
+    /// account, most recent follows first
+    async fn follow_account_uris(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uri>, FollowError>;
+
+    /// Returns the inboxes to deliver the account's activities to, preferring the shared inboxes
+    /// of the followers
+    async fn follower_inboxes(&self, account_id: AccountId) -> Result<Vec<Uri>, FollowError>;
 }
 
 #[async_trait]
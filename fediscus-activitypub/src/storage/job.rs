@@ -0,0 +1,61 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum JobError {
+    #[error("Job not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct JobId(i64);
+
+impl From<i64> for JobId {
+    fn from(id: i64) -> Self {
+        JobId(id)
+    }
+}
+
+/// What a job does with its target
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq)]
+#[sqlx(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Imports replies to the blog post with the target URL
+    Backfill,
+    /// Re-fetches the remote account with the target URI
+    RefreshAccount,
+}
+
+/// A job requested through the admin API
+#[derive(Debug, Clone)]
+pub struct Job {
+    pub id: JobId,
+    pub created_at: NaiveDateTime,
+    pub kind: JobKind,
+    pub target: String,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    /// Why the job failed, not set for jobs that succeeded
+    pub error: Option<String>,
+}
+
+#[async_trait]
+pub trait JobStorage {
+    async fn new_job(&self, kind: JobKind, target: &str) -> Result<Job, JobError>;
+
+    async fn job_by_id(&self, id: JobId) -> Result<Option<Job>, JobError>;
+
+    /// Marks the oldest job that hasn't been started yet as started and returns it
+    async fn start_next_job(&self) -> Result<Option<Job>, JobError>;
+
+    /// Marks the job as finished, with the error if it failed
+    async fn finish_job(&self, id: JobId, error: Option<&str>) -> Result<(), JobError>;
+}
@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use thiserror::Error;
+
+use crate::db::Uri;
+
+use super::{AccountId, NoteId};
+
+#[derive(Debug, Error)]
+pub enum LikeError {
+    #[error("Like not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct LikeId(i64);
+
+impl From<i64> for LikeId {
+    fn from(id: i64) -> Self {
+        LikeId(id)
+    }
+}
+
+/// A like of a note by an account
+#[derive(Debug, Clone)]
+pub struct Like {
+    pub id: LikeId,
+    pub created_at: NaiveDateTime,
+    pub note_id: NoteId,
+    pub account_id: AccountId,
+    /// ID of the Like activity
+    pub uri: Uri,
+}
+
+#[async_trait]
+pub trait LikeStorage {
+    /// Records that the account has liked the note and updates the note's like count.
+    ///
+    /// Each account is counted only once per note, repeated likes return the existing record.
+    async fn new_like(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Like, LikeError>;
+
+    async fn like_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Like>, LikeError>;
+
+    async fn likes_by_note_id(&self, note_id: NoteId) -> Result<Vec<Like>, LikeError>;
+
+    /// Removes the account's like of the note, if any, and updates the note's like count.
+    async fn delete_like(&self, note_id: NoteId, account_id: AccountId) -> Result<(), LikeError>;
+}
@@ -3,15 +3,23 @@
 //
 // SPDX-License-Identifier: MIT
 
-use activitypub_federation::{config::Data, traits::Object};
+use std::collections::HashMap;
+
+use activitypub_federation::{
+    config::Data,
+    kinds::{object::NoteType, public},
+    protocol::verification,
+    traits::Object,
+};
 use async_trait::async_trait;
 use chrono::NaiveDateTime;
+use sqlx::types::Json;
 use thiserror::Error;
 use url::Url;
 
-use crate::{apub, db::Uri, FederationData};
+use crate::{apub, db::Uri, filters::CommentOrigin, FederationData};
 
-use super::{AccountId, BlogId};
+use super::{AccountError, AccountId, BlogId};
 
 #[derive(Debug, Error)]
 pub enum NoteError {
@@ -25,6 +33,12 @@ pub enum NoteError {
     UrlParseError(#[from] url::ParseError),
     #[error("Activity error {0}")]
     ActivityError(#[from] activitypub_federation::error::Error),
+    #[error("Invalid account")]
+    InvalidAccount(#[from] AccountError),
+    #[error("Failed to process note: {0}")]
+    ProcessingError(#[from] Box<crate::activities::ActivityError>),
+    #[error("Note does not belong to any known thread")]
+    NotInThread,
 }
 
 #[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -37,6 +51,25 @@ impl From<i64> for NoteId {
     }
 }
 
+impl From<NoteId> for i64 {
+    fn from(id: NoteId) -> Self {
+        id.0
+    }
+}
+
+/// Whether a note is shown publicly
+#[derive(sqlx::Type, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[sqlx(rename_all = "snake_case")]
+pub enum ModerationState {
+    /// Held for moderation
+    Pending,
+    #[default]
+    Approved,
+    Rejected,
+    /// Approved before, but taken down later
+    Hidden,
+}
+
 #[derive(Debug, Clone)]
 pub struct Note {
     pub id: NoteId,
@@ -49,6 +82,135 @@ pub struct Note {
     pub blog_id: BlogId,
     pub likes: i64,
     pub reposts: i64,
+    pub content: String,
+    pub summary: Option<String>,
+    pub published: Option<NaiveDateTime>,
+    pub language: Option<String>,
+    pub url: Option<Uri>,
+    pub tags: Json<Vec<apub::Tag>>,
+    /// When the note was deleted by its author, deleted notes are kept as tombstones
+    pub deleted_at: Option<NaiveDateTime>,
+    pub moderation_state: ModerationState,
+}
+
+impl Note {
+    pub fn is_deleted(&self) -> bool {
+        self.deleted_at.is_some()
+    }
+
+    pub fn is_approved(&self) -> bool {
+        self.moderation_state == ModerationState::Approved
+    }
+}
+
+/// The federated content of a note, as received from the author's instance.
+#[derive(Debug, Clone, Default)]
+pub struct NoteContent {
+    pub content: String,
+    pub summary: Option<String>,
+    pub published: Option<NaiveDateTime>,
+    pub language: Option<String>,
+    pub url: Option<Uri>,
+    pub tags: Vec<apub::Tag>,
+}
+
+impl From<&apub::Note> for NoteContent {
+    fn from(note: &apub::Note) -> Self {
+        Self {
+            content: note.content.clone(),
+            summary: note.summary.clone(),
+            published: note.published.map(|published| published.naive_utc()),
+            language: note.language(),
+            url: note.url.clone().map(Into::into),
+            tags: note.tag.clone(),
+        }
+    }
+}
+
+/// Order of replies to the same note within a thread
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ThreadOrder {
+    #[default]
+    Oldest,
+    Newest,
+    MostLiked,
+}
+
+/// Which part of the (depth-first flattened) thread to return
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ThreadPage {
+    pub offset: usize,
+    /// Maximum number of notes to return, all remaining notes if not set
+    pub limit: Option<usize>,
+}
+
+/// A note placed within a thread
+#[derive(Debug, Clone)]
+pub struct ThreadNote {
+    pub note: Note,
+    /// Depth of the note in the reply tree, root notes have depth 0
+    pub depth: usize,
+}
+
+/// A page of a thread, ordered as a depth-first walk of the reply tree
+#[derive(Debug, Clone)]
+pub struct Thread {
+    /// Total number of notes in the whole thread
+    pub total: usize,
+    pub notes: Vec<ThreadNote>,
+}
+
+impl Thread {
+    /// Arranges the notes into a reply tree, with replies to the same note sorted by `order`,
+    /// and returns the requested page of the tree walked depth-first.
+    ///
+    /// Top-level notes are the roots of the tree. Replies whose parent is not among `notes`
+    /// are left out together with their own replies, so that leaving out a note that isn't
+    /// shown publicly hides the whole branch below it.
+    pub fn build(notes: Vec<Note>, order: ThreadOrder, page: ThreadPage) -> Self {
+        let mut children: HashMap<Option<NoteId>, Vec<Note>> = HashMap::new();
+        for note in notes {
+            children.entry(note.reply_to_id).or_default().push(note);
+        }
+        for siblings in children.values_mut() {
+            Self::sort(siblings, order);
+        }
+
+        let mut flattened = Vec::new();
+        let mut stack: Vec<(Note, usize)> = children
+            .remove(&None)
+            .unwrap_or_default()
+            .into_iter()
+            .rev()
+            .map(|note| (note, 0))
+            .collect();
+        while let Some((note, depth)) = stack.pop() {
+            if let Some(replies) = children.remove(&Some(note.id)) {
+                stack.extend(replies.into_iter().rev().map(|reply| (reply, depth + 1)));
+            }
+            flattened.push(ThreadNote { note, depth });
+        }
+
+        Self {
+            total: flattened.len(),
+            notes: flattened
+                .into_iter()
+                .skip(page.offset)
+                .take(page.limit.unwrap_or(usize::MAX))
+                .collect(),
+        }
+    }
+
+    fn sort(notes: &mut [Note], order: ThreadOrder) {
+        let published = |note: &Note| (note.published.unwrap_or(note.created_at), note.id.0);
+        match order {
+            ThreadOrder::Oldest => notes.sort_by_key(published),
+            ThreadOrder::Newest => notes.sort_by_key(|note| std::cmp::Reverse(published(note))),
+            ThreadOrder::MostLiked => {
+                notes.sort_by_key(|note| (std::cmp::Reverse(note.likes), published(note)))
+            }
+        }
+    }
 }
 
 #[async_trait]
@@ -58,29 +220,74 @@ impl Object for Note {
     type Error = NoteError;
 
     async fn read_from_id(
-        _id: Url,
-        _data: &Data<Self::DataType>,
+        object_id: Url,
+        data: &Data<Self::DataType>,
     ) -> Result<Option<Self>, Self::Error> {
-        todo!()
+        data.service.storage().post_by_uri(&object_id.into()).await
     }
 
-    async fn into_json(self, _data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
-        todo!()
+    async fn into_json(self, data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
+        let storage = data.service.storage();
+        let author = storage
+            .account_by_id(self.account_id)
+            .await?
+            .ok_or(AccountError::NotFound)?;
+        let in_reply_to = match self.reply_to_id {
+            Some(id) => Some(
+                storage
+                    .post_by_id(id)
+                    .await?
+                    .ok_or(NoteError::NotFound)?
+                    .uri
+                    .into(),
+            ),
+            None => None,
+        };
+        let content_map = self
+            .language
+            .map(|language| HashMap::from([(language, self.content.clone())]));
+        // We only know the audience of our own notes, which are always public
+        let (to, cc) = if author.local {
+            (vec![public()], author.followers_url().into_iter().collect())
+        } else {
+            (Vec::new(), Vec::new())
+        };
+
+        Ok(apub::Note {
+            r#type: NoteType::Note,
+            id: self.uri.into(),
+            in_reply_to,
+            published: self.published.map(|published| published.and_utc()),
+            url: self.url.map(Into::into),
+            attributed_to: author.uri.into(),
+            to,
+            cc,
+            summary: self.summary,
+            content: self.content,
+            content_map,
+            tag: self.tags.0,
+            replies: None,
+        })
     }
 
-    async fn from_json(
-        _json: Self::Kind,
-        _data: &Data<Self::DataType>,
-    ) -> Result<Self, Self::Error> {
-        todo!()
+    /// Stores a note fetched from a remote server, provided it belongs to a thread we track,
+    /// that is it either replies to a known note or is a top-level note about a blog post.
+    async fn from_json(json: Self::Kind, data: &Data<Self::DataType>) -> Result<Self, Self::Error> {
+        let account = json.attributed_to.dereference(data).await?;
+        json.process_new_note(data, &account, CommentOrigin::Imported)
+            .await
+            .map_err(Box::new)?
+            .ok_or(NoteError::NotInThread)
     }
 
     async fn verify(
-        _json: &Self::Kind,
-        _expected_domain: &Url,
+        json: &Self::Kind,
+        expected_domain: &Url,
         _data: &Data<Self::DataType>,
     ) -> Result<(), Self::Error> {
-        todo!()
+        verification::verify_domains_match(json.id.inner(), expected_domain)?;
+        verification::verify_domains_match(json.attributed_to.inner(), json.id.inner())?;
+        Ok(())
     }
 }
 
@@ -93,6 +300,8 @@ pub trait NoteStorage {
         reply_to_id: Option<NoteId>,
         root_id: Option<NoteId>,
         blog_id: BlogId,
+        content: NoteContent,
+        moderation_state: ModerationState,
     ) -> Result<Note, NoteError>;
 
     async fn post_by_id(&self, id: NoteId) -> Result<Option<Note>, NoteError>;
@@ -101,9 +310,157 @@ pub trait NoteStorage {
 
     async fn delete_post_by_id(&self, id: NoteId) -> Result<(), NoteError>;
 
+    /// Replaces the content of the post with the given one and bumps its `updated_at`.
+    async fn update_post(&self, id: NoteId, content: NoteContent) -> Result<Note, NoteError>;
+
+    /// Turns the post into a tombstone: its content is erased, but it keeps its place in the thread.
+    async fn tombstone_post(&self, id: NoteId) -> Result<(), NoteError>;
+
+    /// Moves the thread starting at the given root note under a different blog post.
+    async fn move_thread_to_blog(&self, root_id: NoteId, blog_id: BlogId) -> Result<(), NoteError>;
+
+    /// Returns the thread consisting of all publicly shown notes discussing the given blog post.
+    ///
+    /// Only approved notes are included, and only as long as the notes they reply to are.
+    async fn thread_by_blog_id(
+        &self,
+        blog_id: BlogId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError>;
+
+    /// Returns the thread consisting of the given root note and all replies to it, including
+    /// only the publicly shown notes like [`NoteStorage::thread_by_blog_id`].
+    async fn thread_by_root_id(
+        &self,
+        root_id: NoteId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError>;
+
     async fn post_count(&self) -> Result<usize, NoteError>;
 
-    async fn like_post(&self, post_uri: &Uri) -> Result<(), NoteError>;
+    async fn set_moderation_state(
+        &self,
+        id: NoteId,
+        state: ModerationState,
+    ) -> Result<(), NoteError>;
+
+    /// Returns all notes in the given moderation state, oldest first.
+    async fn posts_by_moderation_state(
+        &self,
+        state: ModerationState,
+    ) -> Result<Vec<Note>, NoteError>;
+
+    /// Whether the account has at least one approved note.
+    async fn has_approved_posts(&self, account_id: AccountId) -> Result<bool, NoteError>;
+}
+
+#[cfg(test)]
+mod tests {
+    use activitypub_federation::fetch::object_id::ObjectId;
+    use serial_test::serial;
+
+    use super::*;
+    use crate::testing::server::{listen, new_instance, DbPost};
+    use crate::testing::{federation_config, MemoryStorage};
+
+    #[tokio::test]
+    #[serial]
+    async fn test_dereference_remote_note() {
+        let test_server = new_instance("localhost:8089", "testuser".to_string())
+            .await
+            .unwrap();
+        listen(&test_server).unwrap();
+        let user = test_server.local_user();
+        let post = DbPost::new(
+            "My new post! https://example.com/blog-post #fediscus".to_string(),
+            user.ap_id.clone(),
+        )
+        .unwrap();
+        user.post(post.clone(), &test_server.to_request_data())
+            .await
+            .unwrap();
+        let reply = DbPost::new_reply(
+            "Replying to myself".to_string(),
+            user.ap_id.clone(),
+            post.ap_id.clone(),
+        )
+        .unwrap();
+        user.post(reply.clone(), &test_server.to_request_data())
+            .await
+            .unwrap();
+        let unrelated = DbPost::new("Just a random note".to_string(), user.ap_id.clone()).unwrap();
+        user.post(unrelated.clone(), &test_server.to_request_data())
+            .await
+            .unwrap();
+
+        let federation = federation_config(MemoryStorage::new("example.com")).await;
+        let data = federation.to_request_data();
+
+        // A top-level note about a blog post is fetched and stored
+        let note = ObjectId::<Note>::from(post.ap_id.inner().clone())
+            .dereference(&data)
+            .await
+            .unwrap();
+        assert_eq!(
+            note.content,
+            "My new post! https://example.com/blog-post #fediscus"
+        );
+        assert_eq!(note.reply_to_id, None);
+        let blog = data
+            .service
+            .storage()
+            .blog_by_url(&Url::parse("https://example.com/blog-post").unwrap())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(note.blog_id, blog.id);
 
-    async fn unlike_post(&self, post_uri: &Uri) -> Result<(), NoteError>;
+        // A reply to a known note is stored as part of the thread
+        let reply_note = ObjectId::<Note>::from(reply.ap_id.inner().clone())
+            .dereference(&data)
+            .await
+            .unwrap();
+        assert_eq!(reply_note.reply_to_id, Some(note.id));
+        assert_eq!(reply_note.root_id, Some(note.id));
+        assert_eq!(reply_note.blog_id, blog.id);
+
+        // Stored notes can be turned back into their ActivityPub representation
+        let json = reply_note.into_json(&data).await.unwrap();
+        assert_eq!(json.id.inner(), reply.ap_id.inner());
+        assert_eq!(json.attributed_to.inner(), user.ap_id.inner());
+        assert_eq!(
+            json.in_reply_to.map(|id| id.into_inner()),
+            Some(post.ap_id.inner().clone())
+        );
+        assert_eq!(json.content, "Replying to myself");
+
+        // A note that does not belong to any thread is not stored
+        let result = ObjectId::<Note>::from(unrelated.ap_id.inner().clone())
+            .dereference(&data)
+            .await;
+        assert!(matches!(result, Err(NoteError::NotInThread)));
+        assert_eq!(data.service.storage().post_count().await.unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_verify_rejects_foreign_domain() {
+        let federation = federation_config(MemoryStorage::new("example.com")).await;
+        let json: apub::Note = serde_json::from_value(serde_json::json!({
+            "type": "Note",
+            "id": "https://example.com/notes/1",
+            "attributedTo": "https://malicious.com/users/evil",
+            "content": "Hello",
+        }))
+        .unwrap();
+
+        let expected_domain = Url::parse("https://example.com/notes/1").unwrap();
+        let result = Note::verify(&json, &expected_domain, &federation.to_request_data()).await;
+        assert!(result.is_err());
+
+        let other_domain = Url::parse("https://other.com/").unwrap();
+        let result = Note::verify(&json, &other_domain, &federation.to_request_data()).await;
+        assert!(result.is_err());
+    }
 }
@@ -0,0 +1,69 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
+use thiserror::Error;
+
+use crate::db::Uri;
+
+use super::AccountId;
+
+#[derive(Debug, Error)]
+pub enum OutboxError {
+    #[error("Outbox activity not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct OutboxActivityId(i64);
+
+impl From<i64> for OutboxActivityId {
+    fn from(id: i64) -> Self {
+        OutboxActivityId(id)
+    }
+}
+
+/// An activity sent by one of our accounts
+#[derive(Debug, Clone)]
+pub struct OutboxActivity {
+    pub id: OutboxActivityId,
+    pub created_at: NaiveDateTime,
+    pub account_id: AccountId,
+    /// ID of the activity
+    pub uri: Uri,
+    /// The activity as it was sent
+    pub activity: Json<serde_json::Value>,
+    /// Whether the activity is listed in the outbox
+    pub public: bool,
+}
+
+#[async_trait]
+pub trait OutboxStorage {
+    /// Records an activity sent by the account, only `public` activities are listed in the outbox.
+    ///
+    /// Recording the same activity again returns the existing record.
+    async fn new_outbox_activity(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        activity: serde_json::Value,
+        public: bool,
+    ) -> Result<OutboxActivity, OutboxError>;
+
+    /// Returns the number of public activities sent by the account
+    async fn outbox_count(&self, account_id: AccountId) -> Result<usize, OutboxError>;
+
+    /// Returns the public activities sent by the account, newest first
+    async fn outbox_activities(
+        &self,
+        account_id: AccountId,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<OutboxActivity>, OutboxError>;
+}
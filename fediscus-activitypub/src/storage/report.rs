@@ -0,0 +1,64 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
+use thiserror::Error;
+use url::Url;
+
+use crate::db::Uri;
+
+use super::AccountId;
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error("Report not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct ReportId(i64);
+
+impl From<i64> for ReportId {
+    fn from(id: i64) -> Self {
+        ReportId(id)
+    }
+}
+
+/// A report of abusive content sent to us by another instance
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub id: ReportId,
+    pub created_at: NaiveDateTime,
+    /// The account that sent the report
+    pub account_id: AccountId,
+    /// ID of the Flag activity
+    pub uri: Uri,
+    /// Comment of the reporter
+    pub content: Option<String>,
+    /// The reported accounts and notes
+    pub objects: Json<Vec<Url>>,
+    pub resolved_at: Option<NaiveDateTime>,
+}
+
+#[async_trait]
+pub trait ReportStorage {
+    /// Records a report received from the account.
+    ///
+    /// Recording the same report again returns the existing record.
+    async fn new_report(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        content: Option<&str>,
+        objects: &[Url],
+    ) -> Result<Report, ReportError>;
+
+    /// Returns the reports that haven't been resolved yet, oldest first
+    async fn unresolved_reports(&self) -> Result<Vec<Report>, ReportError>;
+}
@@ -0,0 +1,68 @@
+// SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+//
+// SPDX-License-Identifier: MIT
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use thiserror::Error;
+
+use crate::db::Uri;
+
+use super::{AccountId, NoteId};
+
+#[derive(Debug, Error)]
+pub enum RepostError {
+    #[error("Repost not found")]
+    NotFound,
+    #[error("Sql Error: {0}")]
+    SqlError(#[from] sqlx::Error),
+}
+
+#[derive(sqlx::Type, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[sqlx(transparent)]
+pub struct RepostId(i64);
+
+impl From<i64> for RepostId {
+    fn from(id: i64) -> Self {
+        RepostId(id)
+    }
+}
+
+/// A repost (boost) of a note by an account
+#[derive(Debug, Clone)]
+pub struct Repost {
+    pub id: RepostId,
+    pub created_at: NaiveDateTime,
+    pub note_id: NoteId,
+    pub account_id: AccountId,
+    /// ID of the Announce activity
+    pub uri: Uri,
+}
+
+#[async_trait]
+pub trait RepostStorage {
+    /// Records that the account has reposted the note and updates the note's repost count.
+    ///
+    /// Each account is counted only once per note, repeated reposts return the existing record.
+    async fn new_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Repost, RepostError>;
+
+    async fn repost_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Repost>, RepostError>;
+
+    async fn reposts_by_note_id(&self, note_id: NoteId) -> Result<Vec<Repost>, RepostError>;
+
+    /// Removes the account's repost of the note, if any, and updates the note's repost count.
+    async fn delete_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<(), RepostError>;
+}
@@ -1,4 +1,29 @@
 mod memory_storage;
 pub mod server;
 
+use std::sync::Arc;
+
+use activitypub_federation::config::FederationConfig;
+
 pub use memory_storage::{create_person, MemoryStorage};
+
+use crate::filters::FilterChain;
+use crate::{FederationData, Service};
+
+/// Builds a federation config backed by the given storage that can talk to the [`server`]
+pub async fn federation_config(storage: MemoryStorage) -> FederationConfig<FederationData> {
+    let config = crate::Config::load().unwrap();
+    let data = FederationData {
+        filters: Arc::new(FilterChain::from_config(&config.filters).unwrap()),
+        config,
+        service: Arc::new(Box::new(Service::new(storage))),
+    };
+    FederationConfig::builder()
+        .app_data(data)
+        .debug(true)
+        .allow_http_urls(true)
+        .domain("example.com")
+        .build()
+        .await
+        .unwrap()
+}
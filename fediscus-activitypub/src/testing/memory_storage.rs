@@ -1,27 +1,41 @@
 use async_trait::async_trait;
+use std::collections::HashMap;
 use std::sync::atomic::AtomicI64;
 
 use crate::apub;
 use crate::db::Uri;
 use crate::storage::{
-    Account, AccountError, AccountId, AccountStorage, Blog, BlogError, BlogId, BlogStorage, Follow,
-    FollowError, FollowId, FollowStorage, Note, NoteError, NoteId, NoteStorage, Storage,
+    is_subdomain, Account, AccountError, AccountId, AccountStorage, ActorBlock, BlockError,
+    BlockStorage, Blog, BlogError, BlogId, BlogStorage, DomainBlock, Follow, FollowDirection,
+    FollowError, FollowId, FollowStorage, Job, JobError, JobId, JobKind, JobStorage, Like,
+    LikeError, LikeStorage, ModerationPolicy, ModerationState, Note, NoteContent, NoteError,
+    NoteId, NoteStorage, OutboxActivity, OutboxError, OutboxStorage, Report, ReportError,
+    ReportStorage, Repost, RepostError, RepostStorage, Storage, Thread, ThreadOrder, ThreadPage,
 };
 use activitypub_federation::fetch::object_id::ObjectId;
-use activitypub_federation::kinds::actor::PersonType;
 use activitypub_federation::protocol::public_key::PublicKey;
-use chrono::Utc;
+use chrono::{NaiveDateTime, Utc};
+use sqlx::types::Json;
 use tokio::sync::Mutex;
 use url::Url;
 
 pub fn create_person(name: &str, domain: &str) -> apub::Person {
     apub::Person {
-        kind: PersonType::Person,
+        kind: apub::ActorType::Person,
         id: ObjectId::<Account>::parse(&format!("http://{}/users/{}", domain, name)).unwrap(),
         preferred_username: name.to_string(),
+        name: None,
+        icon: None,
+        url: None,
+        also_known_as: Vec::new(),
+        published: None,
         inbox: Url::parse(&format!("http://{}/inbox", domain)).unwrap(),
         outbox: Some(Url::parse(&format!("http://{}/outbox", domain)).unwrap()),
-        shared_inbox: Some(Url::parse(&format!("http://{}/shared_inbox", domain)).unwrap()),
+        endpoints: Some(apub::Endpoints {
+            shared_inbox: Some(Url::parse(&format!("http://{}/shared_inbox", domain)).unwrap()),
+        }),
+        followers: None,
+        following: None,
         public_key: PublicKey {
             id: format!("http://{}/users/{}/main-key", domain, name),
             owner: Url::parse(&format!("http://{}/users/{}", domain, name)).unwrap(),
@@ -63,7 +77,7 @@ pub fn generate_local_user(host: &str) -> Account {
             .unwrap()
             .into(),
         outbox: Some(
-            Url::parse(&format!("http://{}/outbox", host))
+            Url::parse(&format!("http://{}/users/fediscus/outbox", host))
                 .unwrap()
                 .into(),
         ),
@@ -71,6 +85,12 @@ pub fn generate_local_user(host: &str) -> Account {
         public_key: TEST_USER_RSA_PUBLIC_KEY.to_string(),
         private_key: Some(TEST_USER_RSA_PRIVATE_KEY.to_string()),
         local: true,
+        display_name: Some("Fediscus".to_string()),
+        avatar_url: None,
+        url: None,
+        bot: false,
+        published: None,
+        moved_to_id: None,
     }
 }
 
@@ -79,11 +99,24 @@ pub struct MemoryStorage {
     follows: Mutex<Vec<Follow>>,
     blogs: Mutex<Vec<Blog>>,
     notes: Mutex<Vec<Note>>,
+    reposts: Mutex<Vec<Repost>>,
+    likes: Mutex<Vec<Like>>,
+    outbox: Mutex<Vec<OutboxActivity>>,
+    domain_blocks: Mutex<Vec<DomainBlock>>,
+    actor_blocks: Mutex<Vec<ActorBlock>>,
+    jobs: Mutex<Vec<Job>>,
+    reports: Mutex<Vec<Report>>,
 
     next_account_id: AtomicI64,
     next_follow_id: AtomicI64,
     next_blog_id: AtomicI64,
     next_note_id: AtomicI64,
+    next_repost_id: AtomicI64,
+    next_like_id: AtomicI64,
+    next_outbox_activity_id: AtomicI64,
+    next_block_id: AtomicI64,
+    next_job_id: AtomicI64,
+    next_report_id: AtomicI64,
 }
 
 impl MemoryStorage {
@@ -93,11 +126,24 @@ impl MemoryStorage {
             follows: Mutex::new(Vec::new()),
             blogs: Mutex::new(Vec::new()),
             notes: Mutex::new(Vec::new()),
+            reposts: Mutex::new(Vec::new()),
+            likes: Mutex::new(Vec::new()),
+            outbox: Mutex::new(Vec::new()),
+            domain_blocks: Mutex::new(Vec::new()),
+            actor_blocks: Mutex::new(Vec::new()),
+            jobs: Mutex::new(Vec::new()),
+            reports: Mutex::new(Vec::new()),
 
             next_account_id: AtomicI64::new(2),
             next_follow_id: AtomicI64::new(1),
             next_blog_id: AtomicI64::new(1),
             next_note_id: AtomicI64::new(1),
+            next_repost_id: AtomicI64::new(1),
+            next_like_id: AtomicI64::new(1),
+            next_outbox_activity_id: AtomicI64::new(1),
+            next_block_id: AtomicI64::new(1),
+            next_job_id: AtomicI64::new(1),
+            next_report_id: AtomicI64::new(1),
         }
     }
 }
@@ -125,10 +171,16 @@ impl AccountStorage for MemoryStorage {
             host: "localhost".to_string(), // Simplified
             inbox: person.inbox.clone().into(),
             outbox: person.outbox.clone().map(Into::into),
-            shared_inbox: person.shared_inbox.clone().map(Into::into),
+            shared_inbox: person.shared_inbox().cloned().map(Into::into),
             public_key: person.public_key.public_key_pem.clone(),
             private_key: None,
             local: false,
+            display_name: person.name.clone(),
+            avatar_url: person.icon.as_ref().map(|icon| icon.url.clone().into()),
+            url: person.url.clone().map(Into::into),
+            bot: person.is_bot(),
+            published: person.published.map(|published| published.naive_utc()),
+            moved_to_id: None,
         };
 
         accounts.push(account.clone());
@@ -165,6 +217,33 @@ impl AccountStorage for MemoryStorage {
             .ok_or(AccountError::NotFound)
     }
 
+    async fn accounts_updated_before(
+        &self,
+        before: NaiveDateTime,
+    ) -> Result<Vec<Account>, AccountError> {
+        let mut accounts: Vec<Account> = self
+            .accounts
+            .lock()
+            .await
+            .iter()
+            .filter(|a| !a.local && a.updated_at < before)
+            .cloned()
+            .collect();
+        accounts.sort_by_key(|a| a.updated_at);
+        Ok(accounts)
+    }
+
+    async fn accounts_by_domain(&self, domain: &str) -> Result<Vec<Account>, AccountError> {
+        Ok(self
+            .accounts
+            .lock()
+            .await
+            .iter()
+            .filter(|a| !a.local && is_subdomain(&a.host, domain))
+            .cloned()
+            .collect())
+    }
+
     async fn update_or_insert_account(
         &self,
         person: &apub::Person,
@@ -175,8 +254,13 @@ impl AccountStorage for MemoryStorage {
             account.username = person.preferred_username.clone();
             account.inbox = person.inbox.clone().into();
             account.outbox = person.outbox.clone().map(Into::into);
-            account.shared_inbox = person.shared_inbox.clone().map(Into::into);
+            account.shared_inbox = person.shared_inbox().cloned().map(Into::into);
             account.public_key = person.public_key.public_key_pem.clone();
+            account.display_name = person.name.clone();
+            account.avatar_url = person.icon.as_ref().map(|icon| icon.url.clone().into());
+            account.url = person.url.clone().map(Into::into);
+            account.bot = person.is_bot();
+            account.published = person.published.map(|published| published.naive_utc());
             account.updated_at = Utc::now().naive_utc();
             Ok(account.clone())
         } else {
@@ -192,10 +276,16 @@ impl AccountStorage for MemoryStorage {
                 host: "localhost".to_string(), // Simplified
                 inbox: person.inbox.clone().into(),
                 outbox: person.outbox.clone().map(Into::into),
-                shared_inbox: person.shared_inbox.clone().map(Into::into),
+                shared_inbox: person.shared_inbox().cloned().map(Into::into),
                 public_key: person.public_key.public_key_pem.clone(),
                 private_key: None,
                 local: false,
+                display_name: person.name.clone(),
+                avatar_url: person.icon.as_ref().map(|icon| icon.url.clone().into()),
+                url: person.url.clone().map(Into::into),
+                bot: person.is_bot(),
+                published: person.published.map(|published| published.naive_utc()),
+                moved_to_id: None,
             };
             accounts.push(account.clone());
             Ok(account)
@@ -221,6 +311,81 @@ impl AccountStorage for MemoryStorage {
             Err(AccountError::NotFound)
         }
     }
+
+    async fn move_account(&self, from: AccountId, to: AccountId) -> Result<(), AccountError> {
+        let mut accounts = self.accounts.lock().await;
+        let mut follows = self.follows.lock().await;
+
+        let targets: Vec<AccountId> = follows
+            .iter()
+            .filter(|f| f.account_id == to)
+            .map(|f| f.target_account_id)
+            .collect();
+        for follow in follows.iter_mut() {
+            if follow.account_id == from && !targets.contains(&follow.target_account_id) {
+                follow.account_id = to;
+            }
+        }
+        follows.retain(|f| f.account_id != from && f.target_account_id != from);
+
+        if let Some(account) = accounts.iter_mut().find(|a| a.id == from) {
+            account.moved_to_id = Some(to);
+            account.updated_at = Utc::now().naive_utc();
+        }
+        Ok(())
+    }
+
+    async fn purge_account(&self, id: AccountId) -> Result<(), AccountError> {
+        let mut accounts = self.accounts.lock().await;
+        let mut follows = self.follows.lock().await;
+        // Lock in the same order as the like and repost methods
+        let mut likes = self.likes.lock().await;
+        let mut reposts = self.reposts.lock().await;
+        let mut notes = self.notes.lock().await;
+
+        accounts.retain(|a| a.id != id);
+        self.outbox.lock().await.retain(|a| a.account_id != id);
+        self.reports.lock().await.retain(|r| r.account_id != id);
+        follows.retain(|f| f.account_id != id && f.target_account_id != id);
+
+        // Replies of other accounts are re-attached to their nearest ancestor that stays
+        let removed: Vec<NoteId> = notes
+            .iter()
+            .filter(|n| n.account_id == id)
+            .map(|n| n.id)
+            .collect();
+        let parents: HashMap<NoteId, Option<NoteId>> =
+            notes.iter().map(|n| (n.id, n.reply_to_id)).collect();
+        for note in notes.iter_mut().filter(|n| n.account_id != id) {
+            let mut parent = note.reply_to_id;
+            while let Some(p) = parent.filter(|p| removed.contains(p)) {
+                parent = parents[&p];
+            }
+            note.reply_to_id = parent;
+        }
+        notes.retain(|n| n.account_id != id);
+
+        // A thread whose root is gone falls apart into threads rooted at its top-most replies
+        let parents: HashMap<NoteId, Option<NoteId>> =
+            notes.iter().map(|n| (n.id, n.reply_to_id)).collect();
+        for note in notes.iter_mut() {
+            if note.root_id.is_some_and(|r| removed.contains(&r)) {
+                let mut top = note.id;
+                while let Some(p) = parents[&top] {
+                    top = p;
+                }
+                note.root_id = (top != note.id).then_some(top);
+            }
+        }
+
+        likes.retain(|l| l.account_id != id && !removed.contains(&l.note_id));
+        reposts.retain(|r| r.account_id != id && !removed.contains(&r.note_id));
+        for note in notes.iter_mut() {
+            note.likes = likes.iter().filter(|l| l.note_id == note.id).count() as i64;
+            note.reposts = reposts.iter().filter(|r| r.note_id == note.id).count() as i64;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -320,6 +485,72 @@ impl FollowStorage for MemoryStorage {
             Err(FollowError::NotFound)
         }
     }
+
+    async fn follow_count(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+    ) -> Result<usize, FollowError> {
+        Ok(self
+            .follow_account_uris(account_id, direction, 0, usize::MAX)
+            .await?
+            .len())
+    }
+
+    async fn follow_account_uris(
+        &self,
+        account_id: AccountId,
+        direction: FollowDirection,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Uri>, FollowError> {
+        let ids: Vec<AccountId> = self
+            .follows
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .filter(|f| !f.pending)
+            .filter_map(|f| match direction {
+                FollowDirection::Followers if f.target_account_id == account_id => {
+                    Some(f.account_id)
+                }
+                FollowDirection::Following if f.account_id == account_id => {
+                    Some(f.target_account_id)
+                }
+                _ => None,
+            })
+            .skip(offset)
+            .take(limit)
+            .collect();
+        let accounts = self.accounts.lock().await;
+        Ok(ids
+            .iter()
+            .filter_map(|id| accounts.iter().find(|a| a.id == *id))
+            .map(|a| a.uri.clone())
+            .collect())
+    }
+
+    async fn follower_inboxes(&self, account_id: AccountId) -> Result<Vec<Uri>, FollowError> {
+        let follows = self.follows.lock().await;
+        let accounts = self.accounts.lock().await;
+        let mut inboxes: Vec<Uri> = Vec::new();
+        for follow in follows
+            .iter()
+            .filter(|f| !f.pending && f.target_account_id == account_id)
+        {
+            if let Some(account) = accounts.iter().find(|a| a.id == follow.account_id) {
+                let inbox = account
+                    .shared_inbox
+                    .clone()
+                    .unwrap_or_else(|| account.inbox.clone());
+                if !inboxes.contains(&inbox) {
+                    inboxes.push(inbox);
+                }
+            }
+        }
+        Ok(inboxes)
+    }
 }
 
 #[async_trait]
@@ -337,6 +568,7 @@ impl BlogStorage for MemoryStorage {
                 .into(),
             created_at: Utc::now().naive_utc(),
             url: url.clone(),
+            moderation_policy: ModerationPolicy::default(),
         };
         blogs.push(blog.clone());
         Ok(blog)
@@ -365,6 +597,17 @@ impl BlogStorage for MemoryStorage {
             Err(BlogError::NotFound)
         }
     }
+
+    async fn set_moderation_policy(
+        &self,
+        id: BlogId,
+        policy: ModerationPolicy,
+    ) -> Result<(), BlogError> {
+        if let Some(blog) = self.blogs.lock().await.iter_mut().find(|b| b.id == id) {
+            blog.moderation_policy = policy;
+        }
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -376,6 +619,8 @@ impl NoteStorage for MemoryStorage {
         reply_to_id: Option<NoteId>,
         root_id: Option<NoteId>,
         blog_id: BlogId,
+        content: NoteContent,
+        moderation_state: ModerationState,
     ) -> Result<Note, NoteError> {
         let now = Utc::now().naive_utc();
         let mut notes = self.notes.lock().await;
@@ -396,6 +641,14 @@ impl NoteStorage for MemoryStorage {
             blog_id,
             likes: 0,
             reposts: 0,
+            content: content.content,
+            summary: content.summary,
+            published: content.published,
+            language: content.language,
+            url: content.url,
+            tags: Json(content.tags),
+            deleted_at: None,
+            moderation_state,
         };
         notes.push(note.clone());
         Ok(note)
@@ -425,28 +678,508 @@ impl NoteStorage for MemoryStorage {
         }
     }
 
+    async fn update_post(&self, id: NoteId, content: NoteContent) -> Result<Note, NoteError> {
+        let mut notes = self.notes.lock().await;
+        if let Some(note) = notes.iter_mut().find(|n| n.id == id) {
+            note.content = content.content;
+            note.summary = content.summary;
+            note.published = content.published;
+            note.language = content.language;
+            note.url = content.url;
+            note.tags = Json(content.tags);
+            note.updated_at = Utc::now().naive_utc();
+            Ok(note.clone())
+        } else {
+            Err(NoteError::NotFound)
+        }
+    }
+
+    async fn tombstone_post(&self, id: NoteId) -> Result<(), NoteError> {
+        let mut notes = self.notes.lock().await;
+        if let Some(note) = notes.iter_mut().find(|n| n.id == id) {
+            let now = Utc::now().naive_utc();
+            note.content = String::new();
+            note.summary = None;
+            note.language = None;
+            note.tags = Json(Vec::new());
+            note.updated_at = now;
+            note.deleted_at = Some(now);
+            Ok(())
+        } else {
+            Err(NoteError::NotFound)
+        }
+    }
+
+    async fn move_thread_to_blog(&self, root_id: NoteId, blog_id: BlogId) -> Result<(), NoteError> {
+        self.notes
+            .lock()
+            .await
+            .iter_mut()
+            .filter(|n| n.id == root_id || n.root_id == Some(root_id))
+            .for_each(|n| n.blog_id = blog_id);
+        Ok(())
+    }
+
+    async fn thread_by_blog_id(
+        &self,
+        blog_id: BlogId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError> {
+        let notes = self
+            .notes
+            .lock()
+            .await
+            .iter()
+            .filter(|n| n.blog_id == blog_id && n.moderation_state == ModerationState::Approved)
+            .cloned()
+            .collect();
+        Ok(Thread::build(notes, order, page))
+    }
+
+    async fn thread_by_root_id(
+        &self,
+        root_id: NoteId,
+        order: ThreadOrder,
+        page: ThreadPage,
+    ) -> Result<Thread, NoteError> {
+        let notes = self
+            .notes
+            .lock()
+            .await
+            .iter()
+            .filter(|n| n.id == root_id || n.root_id == Some(root_id))
+            .filter(|n| n.moderation_state == ModerationState::Approved)
+            .cloned()
+            .collect();
+        Ok(Thread::build(notes, order, page))
+    }
+
     async fn post_count(&self) -> Result<usize, NoteError> {
         Ok(self.notes.lock().await.len())
     }
 
-    async fn like_post(&self, post_uri: &Uri) -> Result<(), NoteError> {
-        let mut notes = self.notes.lock().await;
-        if let Some(note) = notes.iter_mut().find(|n| n.uri == *post_uri) {
-            note.likes += 1;
-            Ok(())
-        } else {
-            Err(NoteError::NotFound)
+    async fn set_moderation_state(
+        &self,
+        id: NoteId,
+        state: ModerationState,
+    ) -> Result<(), NoteError> {
+        if let Some(note) = self.notes.lock().await.iter_mut().find(|n| n.id == id) {
+            note.moderation_state = state;
+        }
+        Ok(())
+    }
+
+    async fn posts_by_moderation_state(
+        &self,
+        state: ModerationState,
+    ) -> Result<Vec<Note>, NoteError> {
+        Ok(self
+            .notes
+            .lock()
+            .await
+            .iter()
+            .filter(|n| n.moderation_state == state)
+            .cloned()
+            .collect())
+    }
+
+    async fn has_approved_posts(&self, account_id: AccountId) -> Result<bool, NoteError> {
+        Ok(self
+            .notes
+            .lock()
+            .await
+            .iter()
+            .any(|n| n.account_id == account_id && n.is_approved()))
+    }
+}
+
+#[async_trait]
+impl RepostStorage for MemoryStorage {
+    async fn new_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Repost, RepostError> {
+        let mut reposts = self.reposts.lock().await;
+        let repost = match reposts
+            .iter()
+            .find(|r| r.note_id == note_id && r.account_id == account_id)
+        {
+            Some(repost) => repost.clone(),
+            None => {
+                let repost = Repost {
+                    id: self
+                        .next_repost_id
+                        .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                        .into(),
+                    created_at: Utc::now().naive_utc(),
+                    note_id,
+                    account_id,
+                    uri: uri.clone(),
+                };
+                reposts.push(repost.clone());
+                repost
+            }
+        };
+
+        let count = reposts.iter().filter(|r| r.note_id == note_id).count();
+        if let Some(note) = self.notes.lock().await.iter_mut().find(|n| n.id == note_id) {
+            note.reposts = count as i64;
+        }
+        Ok(repost)
+    }
+
+    async fn repost_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Repost>, RepostError> {
+        Ok(self
+            .reposts
+            .lock()
+            .await
+            .iter()
+            .find(|r| r.note_id == note_id && r.account_id == account_id)
+            .cloned())
+    }
+
+    async fn reposts_by_note_id(&self, note_id: NoteId) -> Result<Vec<Repost>, RepostError> {
+        Ok(self
+            .reposts
+            .lock()
+            .await
+            .iter()
+            .filter(|r| r.note_id == note_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_repost(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<(), RepostError> {
+        let mut reposts = self.reposts.lock().await;
+        reposts.retain(|r| r.note_id != note_id || r.account_id != account_id);
+
+        let count = reposts.iter().filter(|r| r.note_id == note_id).count();
+        if let Some(note) = self.notes.lock().await.iter_mut().find(|n| n.id == note_id) {
+            note.reposts = count as i64;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LikeStorage for MemoryStorage {
+    async fn new_like(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+        uri: &Uri,
+    ) -> Result<Like, LikeError> {
+        let mut likes = self.likes.lock().await;
+        let like = match likes
+            .iter()
+            .find(|r| r.note_id == note_id && r.account_id == account_id)
+        {
+            Some(like) => like.clone(),
+            None => {
+                let like = Like {
+                    id: self
+                        .next_like_id
+                        .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                        .into(),
+                    created_at: Utc::now().naive_utc(),
+                    note_id,
+                    account_id,
+                    uri: uri.clone(),
+                };
+                likes.push(like.clone());
+                like
+            }
+        };
+
+        let count = likes.iter().filter(|r| r.note_id == note_id).count();
+        if let Some(note) = self.notes.lock().await.iter_mut().find(|n| n.id == note_id) {
+            note.likes = count as i64;
+        }
+        Ok(like)
+    }
+
+    async fn like_by_ids(
+        &self,
+        note_id: NoteId,
+        account_id: AccountId,
+    ) -> Result<Option<Like>, LikeError> {
+        Ok(self
+            .likes
+            .lock()
+            .await
+            .iter()
+            .find(|r| r.note_id == note_id && r.account_id == account_id)
+            .cloned())
+    }
+
+    async fn likes_by_note_id(&self, note_id: NoteId) -> Result<Vec<Like>, LikeError> {
+        Ok(self
+            .likes
+            .lock()
+            .await
+            .iter()
+            .filter(|r| r.note_id == note_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn delete_like(&self, note_id: NoteId, account_id: AccountId) -> Result<(), LikeError> {
+        let mut likes = self.likes.lock().await;
+        likes.retain(|r| r.note_id != note_id || r.account_id != account_id);
+
+        let count = likes.iter().filter(|r| r.note_id == note_id).count();
+        if let Some(note) = self.notes.lock().await.iter_mut().find(|n| n.id == note_id) {
+            note.likes = count as i64;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxStorage for MemoryStorage {
+    async fn new_outbox_activity(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        activity: serde_json::Value,
+        public: bool,
+    ) -> Result<OutboxActivity, OutboxError> {
+        let mut outbox = self.outbox.lock().await;
+        if let Some(existing) = outbox.iter().find(|a| &a.uri == uri) {
+            return Ok(existing.clone());
+        }
+
+        let outbox_activity = OutboxActivity {
+            id: self
+                .next_outbox_activity_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+            created_at: Utc::now().naive_utc(),
+            account_id,
+            uri: uri.clone(),
+            activity: Json(activity),
+            public,
+        };
+        outbox.push(outbox_activity.clone());
+        Ok(outbox_activity)
+    }
+
+    async fn outbox_count(&self, account_id: AccountId) -> Result<usize, OutboxError> {
+        Ok(self
+            .outbox
+            .lock()
+            .await
+            .iter()
+            .filter(|a| a.account_id == account_id && a.public)
+            .count())
+    }
+
+    async fn outbox_activities(
+        &self,
+        account_id: AccountId,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<OutboxActivity>, OutboxError> {
+        Ok(self
+            .outbox
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .filter(|a| a.account_id == account_id && a.public)
+            .skip(offset)
+            .take(limit)
+            .cloned()
+            .collect())
+    }
+}
+
+#[async_trait]
+impl BlockStorage for MemoryStorage {
+    async fn new_domain_block(
+        &self,
+        domain: &str,
+        reason: Option<&str>,
+    ) -> Result<DomainBlock, BlockError> {
+        let mut blocks = self.domain_blocks.lock().await;
+        if let Some(block) = blocks.iter_mut().find(|b| b.domain == domain) {
+            block.reason = reason.map(ToString::to_string);
+            return Ok(block.clone());
+        }
+
+        let block = DomainBlock {
+            id: self
+                .next_block_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+            created_at: Utc::now().naive_utc(),
+            domain: domain.to_string(),
+            reason: reason.map(ToString::to_string),
+        };
+        blocks.push(block.clone());
+        Ok(block)
+    }
+
+    async fn delete_domain_block(&self, domain: &str) -> Result<(), BlockError> {
+        self.domain_blocks
+            .lock()
+            .await
+            .retain(|b| b.domain != domain);
+        Ok(())
+    }
+
+    async fn domain_blocks(&self) -> Result<Vec<DomainBlock>, BlockError> {
+        Ok(self.domain_blocks.lock().await.clone())
+    }
+
+    async fn new_actor_block(
+        &self,
+        uri: &Uri,
+        reason: Option<&str>,
+    ) -> Result<ActorBlock, BlockError> {
+        let mut blocks = self.actor_blocks.lock().await;
+        if let Some(block) = blocks.iter_mut().find(|b| &b.uri == uri) {
+            block.reason = reason.map(ToString::to_string);
+            return Ok(block.clone());
+        }
+
+        let block = ActorBlock {
+            id: self
+                .next_block_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+            created_at: Utc::now().naive_utc(),
+            uri: uri.clone(),
+            reason: reason.map(ToString::to_string),
+        };
+        blocks.push(block.clone());
+        Ok(block)
+    }
+
+    async fn delete_actor_block(&self, uri: &Uri) -> Result<(), BlockError> {
+        self.actor_blocks.lock().await.retain(|b| &b.uri != uri);
+        Ok(())
+    }
+
+    async fn actor_blocks(&self) -> Result<Vec<ActorBlock>, BlockError> {
+        Ok(self.actor_blocks.lock().await.clone())
+    }
+
+    async fn is_blocked(&self, url: &Url) -> Result<bool, BlockError> {
+        if self
+            .actor_blocks
+            .lock()
+            .await
+            .iter()
+            .any(|b| b.uri.as_url() == url)
+        {
+            return Ok(true);
+        }
+        let Some(host) = url.host_str() else {
+            return Ok(false);
+        };
+        Ok(self
+            .domain_blocks
+            .lock()
+            .await
+            .iter()
+            .any(|b| is_subdomain(host, &b.domain)))
+    }
+}
+
+#[async_trait]
+impl JobStorage for MemoryStorage {
+    async fn new_job(&self, kind: JobKind, target: &str) -> Result<Job, JobError> {
+        let job = Job {
+            id: self
+                .next_job_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+            created_at: Utc::now().naive_utc(),
+            kind,
+            target: target.to_string(),
+            started_at: None,
+            finished_at: None,
+            error: None,
+        };
+        self.jobs.lock().await.push(job.clone());
+        Ok(job)
+    }
+
+    async fn job_by_id(&self, id: JobId) -> Result<Option<Job>, JobError> {
+        Ok(self.jobs.lock().await.iter().find(|j| j.id == id).cloned())
+    }
+
+    async fn start_next_job(&self) -> Result<Option<Job>, JobError> {
+        let mut jobs = self.jobs.lock().await;
+        Ok(jobs.iter_mut().find(|j| j.started_at.is_none()).map(|job| {
+            job.started_at = Some(Utc::now().naive_utc());
+            job.clone()
+        }))
+    }
+
+    async fn finish_job(&self, id: JobId, error: Option<&str>) -> Result<(), JobError> {
+        let mut jobs = self.jobs.lock().await;
+        let job = jobs
+            .iter_mut()
+            .find(|j| j.id == id)
+            .ok_or(JobError::NotFound)?;
+        job.finished_at = Some(Utc::now().naive_utc());
+        job.error = error.map(str::to_string);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ReportStorage for MemoryStorage {
+    async fn new_report(
+        &self,
+        account_id: AccountId,
+        uri: &Uri,
+        content: Option<&str>,
+        objects: &[Url],
+    ) -> Result<Report, ReportError> {
+        let mut reports = self.reports.lock().await;
+        if let Some(existing) = reports.iter().find(|r| &r.uri == uri) {
+            return Ok(existing.clone());
         }
+
+        let report = Report {
+            id: self
+                .next_report_id
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst)
+                .into(),
+            created_at: Utc::now().naive_utc(),
+            account_id,
+            uri: uri.clone(),
+            content: content.map(str::to_string),
+            objects: Json(objects.to_vec()),
+            resolved_at: None,
+        };
+        reports.push(report.clone());
+        Ok(report)
     }
 
-    async fn unlike_post(&self, post_uri: &Uri) -> Result<(), NoteError> {
-        let mut notes = self.notes.lock().await;
-        if let Some(note) = notes.iter_mut().find(|n| n.uri == *post_uri) {
-            note.likes -= 1;
-            Ok(())
-        } else {
-            Err(NoteError::NotFound)
-        }
+    async fn unresolved_reports(&self) -> Result<Vec<Report>, ReportError> {
+        Ok(self
+            .reports
+            .lock()
+            .await
+            .iter()
+            .filter(|r| r.resolved_at.is_none())
+            .cloned()
+            .collect())
     }
 }
 
@@ -521,6 +1254,61 @@ mod test {
         assert_eq!(updated_account.username, "updateduser");
     }
 
+    #[tokio::test]
+    async fn test_update_or_insert_account_profile() {
+        let storage = MemoryStorage::new("example.com");
+        let mut person = create_person("testuser", "example.com");
+        person.name = Some("Test User".to_string());
+        person.icon = Some(apub::Image::new(
+            Url::parse("https://example.com/avatar.png").unwrap(),
+        ));
+        person.url = Some(Url::parse("https://example.com/@testuser").unwrap());
+
+        let account = storage.update_or_insert_account(&person).await.unwrap();
+        assert_eq!(account.display_name.as_deref(), Some("Test User"));
+        assert_eq!(
+            account.avatar_url.map(|url| url.to_string()),
+            Some("https://example.com/avatar.png".to_string())
+        );
+        assert_eq!(
+            account.url.map(|url| url.to_string()),
+            Some("https://example.com/@testuser".to_string())
+        );
+        assert!(!account.bot);
+
+        // Turning the account into a bot and removing the avatar is picked up
+        person.kind = apub::ActorType::Service;
+        person.icon = None;
+        let account = storage.update_or_insert_account(&person).await.unwrap();
+        assert!(account.bot);
+        assert!(account.avatar_url.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_accounts_updated_before() {
+        let storage = MemoryStorage::new("example.com");
+        let account = storage
+            .new_account(&create_person("testuser", "example.com"))
+            .await
+            .unwrap();
+
+        // The local account is never returned
+        let accounts = storage
+            .accounts_updated_before(account.updated_at + chrono::TimeDelta::seconds(1))
+            .await
+            .unwrap();
+        assert_eq!(
+            accounts.iter().map(|a| a.id).collect::<Vec<_>>(),
+            vec![account.id]
+        );
+
+        let accounts = storage
+            .accounts_updated_before(account.updated_at)
+            .await
+            .unwrap();
+        assert!(accounts.is_empty());
+    }
+
     #[tokio::test]
     async fn test_delete_account_by_id() {
         let storage = MemoryStorage::new("example.com");
@@ -551,6 +1339,55 @@ mod test {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_move_account() {
+        let storage = MemoryStorage::new("example.com");
+        let local = storage.get_local_account().await.unwrap();
+        let old = storage
+            .new_account(&create_person("old", "old.example.com"))
+            .await
+            .unwrap();
+        let new = storage
+            .new_account(&create_person("new", "new.example.com"))
+            .await
+            .unwrap();
+        let follow_uri: Uri = Url::parse("http://old.example.com/follow/1")
+            .unwrap()
+            .into();
+        let follow_back_uri: Uri = Url::parse("http://example.com/follow/1").unwrap().into();
+        storage
+            .new_follow(old.id, local.id, &follow_uri, false)
+            .await
+            .unwrap();
+        storage
+            .new_follow(local.id, old.id, &follow_back_uri, false)
+            .await
+            .unwrap();
+
+        storage.move_account(old.id, new.id).await.unwrap();
+
+        // The follow is transferred to the new account
+        let follow = storage
+            .follow_by_ids(new.id, local.id)
+            .await
+            .unwrap()
+            .expect("Follow was not transferred");
+        assert_eq!(follow.uri, follow_uri);
+        // The follow back is dropped and the old account is linked to the new one
+        assert!(storage
+            .follows_by_account_id(old.id)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(storage
+            .follow_by_ids(local.id, old.id)
+            .await
+            .unwrap()
+            .is_none());
+        let old = storage.account_by_id(old.id).await.unwrap().unwrap();
+        assert_eq!(old.moved_to_id, Some(new.id));
+    }
+
     #[tokio::test]
     async fn test_new_follow() {
         let storage = MemoryStorage::new("example.com");
@@ -788,13 +1625,59 @@ mod test {
         let uri = Url::parse("https://example.com/note/1").unwrap();
 
         let note = storage
-            .new_post(account.id, uri.into(), None, None, blog.id)
+            .new_post(
+                account.id,
+                uri.into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
             .await
             .unwrap();
         assert_eq!(note.account_id, account.id);
         assert_eq!(note.blog_id, blog.id);
     }
 
+    #[tokio::test]
+    async fn test_new_post_with_content() {
+        let storage = MemoryStorage::new("example.com");
+        let person = create_person("testuser", "example.com");
+        let account = storage.new_account(&person).await.unwrap();
+        let blog_url = Url::parse("https://example.com/blog").unwrap();
+        let blog = storage.new_blog(&blog_url).await.unwrap();
+        let uri = Url::parse("https://example.com/note/1").unwrap();
+        let url = Url::parse("https://example.com/@testuser/1").unwrap();
+        let content = NoteContent {
+            content: "<p>Hello, world!</p>".to_string(),
+            summary: Some("Greetings".to_string()),
+            published: Some(Utc::now().naive_utc()),
+            language: Some("en".to_string()),
+            url: Some(url.clone().into()),
+            tags: vec![],
+        };
+
+        let note = storage
+            .new_post(
+                account.id,
+                uri.into(),
+                None,
+                None,
+                blog.id,
+                content.clone(),
+                ModerationState::Approved,
+            )
+            .await
+            .unwrap();
+        let result = storage.post_by_id(note.id).await.unwrap().unwrap();
+        assert_eq!(result.content, content.content);
+        assert_eq!(result.summary, content.summary);
+        assert_eq!(result.published, content.published);
+        assert_eq!(result.language, content.language);
+        assert_eq!(result.url, Some(url.into()));
+    }
+
     #[tokio::test]
     async fn test_post_by_id() {
         let storage = MemoryStorage::new("example.com");
@@ -805,7 +1688,15 @@ mod test {
         let uri = Url::parse("https://example.com/note/1").unwrap();
 
         let note = storage
-            .new_post(account.id, uri.into(), None, None, blog.id)
+            .new_post(
+                account.id,
+                uri.into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
             .await
             .unwrap();
         let result = storage.post_by_id(note.id).await.unwrap().unwrap();
@@ -822,7 +1713,15 @@ mod test {
         let uri = Url::parse("https://example.com/note/1").unwrap();
 
         let note = storage
-            .new_post(account.id, uri.clone().into(), None, None, blog.id)
+            .new_post(
+                account.id,
+                uri.clone().into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
             .await
             .unwrap();
         let result = storage.post_by_uri(&uri.into()).await.unwrap().unwrap();
@@ -839,7 +1738,15 @@ mod test {
         let uri = Url::parse("https://example.com/note/1").unwrap();
 
         let note = storage
-            .new_post(account.id, uri.into(), None, None, blog.id)
+            .new_post(
+                account.id,
+                uri.into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
             .await
             .unwrap();
         storage.delete_post_by_id(note.id).await.unwrap();
@@ -847,4 +1754,487 @@ mod test {
         let result = storage.post_by_id(note.id).await.unwrap();
         assert!(result.is_none());
     }
+
+    #[tokio::test]
+    async fn test_moderation_state() {
+        let storage = MemoryStorage::new("example.com");
+        let person = create_person("testuser", "example.com");
+        let account = storage.new_account(&person).await.unwrap();
+        let blog_url = Url::parse("https://example.com/blog").unwrap();
+        let blog = storage.new_blog(&blog_url).await.unwrap();
+        assert_eq!(blog.moderation_policy, ModerationPolicy::AutoApprove);
+        storage
+            .set_moderation_policy(blog.id, ModerationPolicy::HoldAll)
+            .await
+            .unwrap();
+        let blog = storage.blog_by_id(blog.id).await.unwrap().unwrap();
+        assert_eq!(blog.moderation_policy, ModerationPolicy::HoldAll);
+
+        let note = storage
+            .new_post(
+                account.id,
+                Url::parse("https://example.com/note/1").unwrap().into(),
+                None,
+                None,
+                blog.id,
+                NoteContent::default(),
+                ModerationState::Pending,
+            )
+            .await
+            .unwrap();
+        assert!(!note.is_approved());
+        assert!(!storage.has_approved_posts(account.id).await.unwrap());
+        let pending = storage
+            .posts_by_moderation_state(ModerationState::Pending)
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, note.id);
+
+        storage
+            .set_moderation_state(note.id, ModerationState::Approved)
+            .await
+            .unwrap();
+        assert!(storage.has_approved_posts(account.id).await.unwrap());
+        assert!(storage
+            .posts_by_moderation_state(ModerationState::Pending)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    async fn create_thread(storage: &MemoryStorage) -> (Note, Vec<Note>) {
+        let person = create_person("testuser", "example.com");
+        let account = storage.new_account(&person).await.unwrap();
+        let blog_url = Url::parse("https://example.com/blog").unwrap();
+        let blog = storage.new_blog(&blog_url).await.unwrap();
+
+        let post = |n: usize, parent: Option<&Note>| {
+            let uri: Uri = Url::parse(&format!("https://example.com/note/{}", n))
+                .unwrap()
+                .into();
+            let reply_to_id = parent.map(|p| p.id);
+            let root_id = parent.map(|p| p.root_id.unwrap_or(p.id));
+            let published = chrono::DateTime::from_timestamp(1_700_000_000 + n as i64, 0)
+                .unwrap()
+                .naive_utc();
+            storage.new_post(
+                account.id,
+                uri,
+                reply_to_id,
+                root_id,
+                blog.id,
+                NoteContent {
+                    published: Some(published),
+                    ..Default::default()
+                },
+                ModerationState::Approved,
+            )
+        };
+
+        // root
+        // +- reply1
+        // |  +- reply3
+        // +- reply2
+        let root = post(0, None).await.unwrap();
+        let reply1 = post(1, Some(&root)).await.unwrap();
+        let reply2 = post(2, Some(&root)).await.unwrap();
+        let reply3 = post(3, Some(&reply1)).await.unwrap();
+        (root, vec![reply1, reply2, reply3])
+    }
+
+    #[tokio::test]
+    async fn test_thread_by_blog_id() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+
+        let thread = storage
+            .thread_by_blog_id(root.blog_id, ThreadOrder::Oldest, ThreadPage::default())
+            .await
+            .unwrap();
+        assert_eq!(thread.total, 4);
+        let order: Vec<_> = thread.notes.iter().map(|n| (n.note.id, n.depth)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (root.id, 0),
+                (replies[0].id, 1),
+                (replies[2].id, 2),
+                (replies[1].id, 1)
+            ]
+        );
+
+        let thread = storage
+            .thread_by_blog_id(root.blog_id, ThreadOrder::Newest, ThreadPage::default())
+            .await
+            .unwrap();
+        let order: Vec<_> = thread.notes.iter().map(|n| (n.note.id, n.depth)).collect();
+        assert_eq!(
+            order,
+            vec![
+                (root.id, 0),
+                (replies[1].id, 1),
+                (replies[0].id, 1),
+                (replies[2].id, 2)
+            ]
+        );
+
+        // Hiding a reply hides the replies to it as well
+        storage
+            .set_moderation_state(replies[0].id, ModerationState::Hidden)
+            .await
+            .unwrap();
+        let thread = storage
+            .thread_by_blog_id(root.blog_id, ThreadOrder::Oldest, ThreadPage::default())
+            .await
+            .unwrap();
+        assert_eq!(thread.total, 2);
+        let order: Vec<_> = thread.notes.iter().map(|n| (n.note.id, n.depth)).collect();
+        assert_eq!(order, vec![(root.id, 0), (replies[1].id, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_thread_by_root_id_most_liked_paginated() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+        let other = storage
+            .new_account(&create_person("otheruser", "example.com"))
+            .await
+            .unwrap();
+        let like_uri: Uri = Url::parse("https://example.com/activity/1").unwrap().into();
+        storage
+            .new_like(replies[1].id, other.id, &like_uri)
+            .await
+            .unwrap();
+
+        let thread = storage
+            .thread_by_root_id(
+                root.id,
+                ThreadOrder::MostLiked,
+                ThreadPage {
+                    offset: 1,
+                    limit: Some(2),
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(thread.total, 4);
+        let order: Vec<_> = thread.notes.iter().map(|n| (n.note.id, n.depth)).collect();
+        assert_eq!(order, vec![(replies[1].id, 1), (replies[0].id, 1)]);
+    }
+
+    #[tokio::test]
+    async fn test_update_post() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, _) = create_thread(&storage).await;
+
+        let updated = storage
+            .update_post(
+                root.id,
+                NoteContent {
+                    content: "Edited".to_string(),
+                    ..Default::default()
+                },
+            )
+            .await
+            .unwrap();
+        assert_eq!(updated.content, "Edited");
+        assert!(updated.updated_at >= root.updated_at);
+
+        let result = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(result.content, "Edited");
+    }
+
+    #[tokio::test]
+    async fn test_move_thread_to_blog() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+        let blog_url = Url::parse("https://example.com/another-blog").unwrap();
+        let blog = storage.new_blog(&blog_url).await.unwrap();
+
+        storage.move_thread_to_blog(root.id, blog.id).await.unwrap();
+
+        for note in std::iter::once(&root).chain(replies.iter()) {
+            let result = storage.post_by_id(note.id).await.unwrap().unwrap();
+            assert_eq!(result.blog_id, blog.id);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_repost_counted_once_per_account() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, _) = create_thread(&storage).await;
+        let other = storage
+            .new_account(&create_person("otheruser", "example.com"))
+            .await
+            .unwrap();
+        let uri: Uri = Url::parse("https://example.com/activity/1").unwrap().into();
+
+        storage.new_repost(root.id, other.id, &uri).await.unwrap();
+        storage.new_repost(root.id, other.id, &uri).await.unwrap();
+        let note = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(note.reposts, 1);
+        assert_eq!(storage.reposts_by_note_id(root.id).await.unwrap().len(), 1);
+
+        storage.delete_repost(root.id, other.id).await.unwrap();
+        storage.delete_repost(root.id, other.id).await.unwrap();
+        let note = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(note.reposts, 0);
+        assert!(storage
+            .repost_by_ids(root.id, other.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_like_counted_once_per_account() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, _) = create_thread(&storage).await;
+        let other = storage
+            .new_account(&create_person("otheruser", "example.com"))
+            .await
+            .unwrap();
+        let uri: Uri = Url::parse("https://example.com/activity/1").unwrap().into();
+
+        storage.new_like(root.id, other.id, &uri).await.unwrap();
+        storage.new_like(root.id, other.id, &uri).await.unwrap();
+        let note = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(note.likes, 1);
+        assert_eq!(storage.likes_by_note_id(root.id).await.unwrap().len(), 1);
+
+        storage.delete_like(root.id, other.id).await.unwrap();
+        storage.delete_like(root.id, other.id).await.unwrap();
+        let note = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(note.likes, 0);
+        assert!(storage
+            .like_by_ids(root.id, other.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_tombstone_post_keeps_replies() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+
+        storage.tombstone_post(replies[0].id).await.unwrap();
+
+        let tombstone = storage.post_by_id(replies[0].id).await.unwrap().unwrap();
+        assert!(tombstone.is_deleted());
+        assert!(tombstone.content.is_empty());
+        assert_eq!(tombstone.reply_to_id, Some(root.id));
+
+        let thread = storage
+            .thread_by_root_id(root.id, ThreadOrder::Oldest, ThreadPage::default())
+            .await
+            .unwrap();
+        assert_eq!(thread.total, replies.len() + 1);
+    }
+
+    #[tokio::test]
+    async fn test_purge_account() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+        let local = storage.get_local_account().await.unwrap();
+        let other = storage
+            .new_account(&create_person("otheruser", "example.com"))
+            .await
+            .unwrap();
+        let uri: Uri = Url::parse("https://example.com/activity/1").unwrap().into();
+
+        storage
+            .new_follow(other.id, local.id, &uri, false)
+            .await
+            .unwrap();
+        storage.new_like(root.id, other.id, &uri).await.unwrap();
+        storage.new_repost(root.id, other.id, &uri).await.unwrap();
+        let reply = storage
+            .new_post(
+                other.id,
+                Url::parse("https://example.com/note/other").unwrap().into(),
+                Some(replies[0].id),
+                Some(root.id),
+                root.blog_id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
+            .await
+            .unwrap();
+
+        storage.purge_account(other.id).await.unwrap();
+
+        assert!(storage.account_by_id(other.id).await.unwrap().is_none());
+        assert!(storage.post_by_id(reply.id).await.unwrap().is_none());
+        assert!(storage
+            .follow_by_ids(other.id, local.id)
+            .await
+            .unwrap()
+            .is_none());
+        let note = storage.post_by_id(root.id).await.unwrap().unwrap();
+        assert_eq!(note.likes, 0);
+        assert_eq!(note.reposts, 0);
+        assert_eq!(storage.post_count().await.unwrap(), replies.len() + 1);
+
+        // Purging the author of the root note removes all their notes
+        storage.purge_account(root.account_id).await.unwrap();
+        assert_eq!(storage.post_count().await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_purge_account_keeps_replies_of_others() {
+        let storage = MemoryStorage::new("example.com");
+        let (root, replies) = create_thread(&storage).await;
+        let other = storage
+            .new_account(&create_person("otheruser", "example.com"))
+            .await
+            .unwrap();
+        let reply = |n: usize, parent: &Note| {
+            let uri: Uri = Url::parse(&format!("https://example.com/note/other/{}", n))
+                .unwrap()
+                .into();
+            storage.new_post(
+                other.id,
+                uri,
+                Some(parent.id),
+                Some(parent.root_id.unwrap_or(parent.id)),
+                root.blog_id,
+                NoteContent::default(),
+                ModerationState::Approved,
+            )
+        };
+
+        // root
+        // +- reply1
+        // |  +- reply3
+        // |     +- other1
+        // |        +- other2
+        // +- other3
+        let other1 = reply(1, &replies[2]).await.unwrap();
+        let other2 = reply(2, &other1).await.unwrap();
+        let other3 = reply(3, &root).await.unwrap();
+
+        storage.purge_account(root.account_id).await.unwrap();
+
+        assert!(storage.post_by_id(root.id).await.unwrap().is_none());
+        assert_eq!(storage.post_count().await.unwrap(), 3);
+        // The replies of the other account are kept, each starting its own thread
+        let other1 = storage.post_by_id(other1.id).await.unwrap().unwrap();
+        assert_eq!(other1.reply_to_id, None);
+        assert_eq!(other1.root_id, None);
+        let other2 = storage.post_by_id(other2.id).await.unwrap().unwrap();
+        assert_eq!(other2.reply_to_id, Some(other1.id));
+        assert_eq!(other2.root_id, Some(other1.id));
+        let other3 = storage.post_by_id(other3.id).await.unwrap().unwrap();
+        assert_eq!(other3.reply_to_id, None);
+        assert_eq!(other3.root_id, None);
+    }
+
+    #[tokio::test]
+    async fn test_reports() {
+        let storage = MemoryStorage::new("example.com");
+        let person = create_person("reporter", "remote.example");
+        let reporter = storage.new_account(&person).await.unwrap();
+        let uri: Uri = Url::parse("https://remote.example/flag/1").unwrap().into();
+        let objects = vec![Url::parse("https://example.com/notes/1").unwrap()];
+
+        let report = storage
+            .new_report(reporter.id, &uri, Some("Spam"), &objects)
+            .await
+            .unwrap();
+        assert_eq!(report.content.as_deref(), Some("Spam"));
+        assert_eq!(report.objects.0, objects);
+        // Receiving the same report again doesn't duplicate it
+        let again = storage
+            .new_report(reporter.id, &uri, None, &[])
+            .await
+            .unwrap();
+        assert_eq!(again.id, report.id);
+        assert_eq!(storage.unresolved_reports().await.unwrap().len(), 1);
+
+        // Reports are removed together with the reporter
+        storage.purge_account(reporter.id).await.unwrap();
+        assert!(storage.unresolved_reports().await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_outbox_activities() {
+        let storage = MemoryStorage::new("example.com");
+        let local = storage.get_local_account().await.unwrap();
+
+        for i in 1..=3 {
+            let uri: Uri = Url::parse(&format!("https://example.com/activity/{}", i))
+                .unwrap()
+                .into();
+            storage
+                .new_outbox_activity(
+                    local.id,
+                    &uri,
+                    serde_json::json!({ "id": uri.as_str() }),
+                    true,
+                )
+                .await
+                .unwrap();
+        }
+        // Recording the same activity again doesn't duplicate it
+        let uri: Uri = Url::parse("https://example.com/activity/3").unwrap().into();
+        storage
+            .new_outbox_activity(local.id, &uri, serde_json::json!({}), true)
+            .await
+            .unwrap();
+        // Non-public activities are recorded, but not listed
+        let uri: Uri = Url::parse("https://example.com/activity/4").unwrap().into();
+        let private = storage
+            .new_outbox_activity(local.id, &uri, serde_json::json!({}), false)
+            .await
+            .unwrap();
+        assert!(!private.public);
+
+        assert_eq!(storage.outbox_count(local.id).await.unwrap(), 3);
+        let activities = storage.outbox_activities(local.id, 1, 10).await.unwrap();
+        let uris: Vec<_> = activities.iter().map(|a| a.uri.as_str()).collect();
+        assert_eq!(
+            uris,
+            vec![
+                "https://example.com/activity/2",
+                "https://example.com/activity/1"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_follower_inboxes() {
+        let storage = MemoryStorage::new("example.com");
+        let local = storage.get_local_account().await.unwrap();
+        let uri: Uri = Url::parse("https://example.com/activity/1").unwrap().into();
+
+        for name in ["testuser1", "testuser2"] {
+            let account = storage
+                .new_account(&create_person(name, "example.com"))
+                .await
+                .unwrap();
+            storage
+                .new_follow(account.id, local.id, &uri, false)
+                .await
+                .unwrap();
+        }
+        let pending = storage
+            .new_account(&create_person("pending", "other.example.com"))
+            .await
+            .unwrap();
+        storage
+            .new_follow(pending.id, local.id, &uri, true)
+            .await
+            .unwrap();
+
+        // Both followers share the inbox of their instance, pending followers are skipped
+        let inboxes = storage.follower_inboxes(local.id).await.unwrap();
+        assert_eq!(
+            inboxes,
+            vec![Uri::from(
+                Url::parse("http://example.com/shared_inbox").unwrap()
+            )]
+        );
+    }
 }
@@ -1,9 +1,17 @@
 mod accept;
 mod create_post;
+mod delete_user;
+mod flag;
 mod follow;
 mod undo;
+mod update_post;
+mod update_user;
 
 pub use accept::Accept;
 pub use create_post::CreatePost;
+pub use delete_user::DeleteUser;
+pub use flag::Flag;
 pub use follow::Follow;
 pub use undo::Undo;
+pub use update_post::UpdatePost;
+pub use update_user::UpdateUser;
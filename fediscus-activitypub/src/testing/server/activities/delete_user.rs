@@ -0,0 +1,61 @@
+use crate::testing::server::{error::Error, instance::DatabaseHandle, objects::DbUser};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::DeleteType,
+    protocol::{helpers::deserialize_one_or_many, verification::verify_urls_match},
+    traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DeleteUser {
+    pub(crate) actor: ObjectId<DbUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: Url,
+    #[serde(rename = "type")]
+    pub(crate) kind: DeleteType,
+    pub(crate) id: Url,
+}
+
+impl DeleteUser {
+    pub fn new(actor: ObjectId<DbUser>, to: Vec<Url>, id: Url) -> DeleteUser {
+        DeleteUser {
+            object: actor.inner().clone(),
+            actor,
+            to,
+            kind: DeleteType::Delete,
+            id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for DeleteUser {
+    type DataType = DatabaseHandle;
+    type Error = Error;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_urls_match(self.actor.inner(), &self.object)?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        data.users
+            .lock()
+            .unwrap()
+            .retain(|u| u.ap_id.inner() != &self.object);
+        Ok(())
+    }
+}
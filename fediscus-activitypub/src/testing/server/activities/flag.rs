@@ -0,0 +1,60 @@
+use crate::testing::server::{error::Error, instance::DatabaseHandle, objects::DbUser};
+use activitypub_federation::{
+    config::Data, fetch::object_id::ObjectId, kinds::activity::FlagType,
+    protocol::helpers::deserialize_one_or_many, traits::ActivityHandler,
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Deserialize, Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Flag {
+    pub actor: ObjectId<DbUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub object: Vec<Url>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(rename = "type")]
+    kind: FlagType,
+    pub id: Url,
+}
+
+impl Flag {
+    pub fn new(
+        actor: ObjectId<DbUser>,
+        object: Vec<Url>,
+        content: Option<String>,
+        id: Url,
+    ) -> Flag {
+        Flag {
+            actor,
+            object,
+            content,
+            kind: Default::default(),
+            id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for Flag {
+    type DataType = DatabaseHandle;
+    type Error = Error;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, _data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        data.reports.lock().unwrap().push(self);
+        Ok(())
+    }
+}
@@ -0,0 +1,62 @@
+use crate::testing::server::{
+    error::Error,
+    instance::DatabaseHandle,
+    objects::{DbPost, DbUser, Note},
+};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::UpdateType,
+    protocol::helpers::deserialize_one_or_many,
+    traits::{ActivityHandler, Object},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePost {
+    pub(crate) actor: ObjectId<DbUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: Note,
+    #[serde(rename = "type")]
+    pub(crate) kind: UpdateType,
+    pub(crate) id: Url,
+}
+
+impl UpdatePost {
+    pub fn new(note: Note, id: Url) -> UpdatePost {
+        UpdatePost {
+            actor: note.attributed_to.clone(),
+            to: note.to.clone(),
+            object: note,
+            kind: UpdateType::Update,
+            id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UpdatePost {
+    type DataType = DatabaseHandle;
+    type Error = Error;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        DbPost::verify(&self.object, &self.id, data).await?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        DbPost::from_json(self.object, data).await?;
+        Ok(())
+    }
+}
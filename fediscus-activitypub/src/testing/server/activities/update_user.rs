@@ -0,0 +1,63 @@
+use crate::testing::server::{
+    error::Error,
+    instance::DatabaseHandle,
+    objects::{DbUser, Person},
+};
+use activitypub_federation::{
+    config::Data,
+    fetch::object_id::ObjectId,
+    kinds::activity::UpdateType,
+    protocol::{helpers::deserialize_one_or_many, verification::verify_domains_match},
+    traits::{ActivityHandler, Object},
+};
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdateUser {
+    pub(crate) actor: ObjectId<DbUser>,
+    #[serde(deserialize_with = "deserialize_one_or_many")]
+    pub(crate) to: Vec<Url>,
+    pub(crate) object: Person,
+    #[serde(rename = "type")]
+    pub(crate) kind: UpdateType,
+    pub(crate) id: Url,
+}
+
+impl UpdateUser {
+    pub fn new(actor: ObjectId<DbUser>, to: Vec<Url>, object: Person, id: Url) -> UpdateUser {
+        UpdateUser {
+            actor,
+            to,
+            object,
+            kind: UpdateType::Update,
+            id,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl ActivityHandler for UpdateUser {
+    type DataType = DatabaseHandle;
+    type Error = Error;
+
+    fn id(&self) -> &Url {
+        &self.id
+    }
+
+    fn actor(&self) -> &Url {
+        self.actor.inner()
+    }
+
+    async fn verify(&self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        verify_domains_match(self.actor.inner(), &self.id)?;
+        DbUser::verify(&self.object, &self.id, data).await?;
+        Ok(())
+    }
+
+    async fn receive(self, data: &Data<Self::DataType>) -> Result<(), Self::Error> {
+        DbUser::from_json(self.object, data).await?;
+        Ok(())
+    }
+}
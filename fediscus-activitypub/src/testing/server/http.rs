@@ -1,7 +1,8 @@
 use crate::testing::server::{
     error::Error,
     instance::DatabaseHandle,
-    objects::{DbUser, Person, PersonAcceptedActivities},
+    objects::{DbPost, DbUser, Note, Person, PersonAcceptedActivities, Replies},
+    utils::generate_object_url,
 };
 use activitypub_federation::{
     axum::{
@@ -13,6 +14,7 @@ use activitypub_federation::{
     protocol::context::WithContext,
     traits::Object,
 };
+use anyhow::anyhow;
 use axum::{
     extract::{Path, Query},
     response::IntoResponse,
@@ -29,6 +31,8 @@ pub fn listen(config: &FederationConfig<DatabaseHandle>) -> Result<(), Error> {
     let config = config.clone();
     let app = Router::new()
         .route("/:user/inbox", post(http_post_user_inbox))
+        .route("/objects/:id", get(http_get_object))
+        .route("/objects/:id/replies", get(http_get_replies))
         .route("/:user", get(http_get_user))
         .route("/.well-known/webfinger", get(webfinger))
         .layer(FederationMiddleware::new(config));
@@ -57,6 +61,31 @@ async fn http_get_user(
     Ok(FederationJson(WithContext::new_default(json_user)))
 }
 
+async fn http_get_object(
+    Path(id): Path<String>,
+    data: Data<DatabaseHandle>,
+) -> Result<FederationJson<WithContext<Note>>, Error> {
+    let object_id = generate_object_url(data.domain(), &id)?;
+    let post = DbPost::read_from_id(object_id, &data)
+        .await?
+        .ok_or_else(|| anyhow!("Object not found"))?;
+    let json_post = post.into_json(&data).await?;
+    Ok(FederationJson(WithContext::new_default(json_post)))
+}
+
+async fn http_get_replies(
+    Path(id): Path<String>,
+    data: Data<DatabaseHandle>,
+) -> Result<FederationJson<WithContext<Replies>>, Error> {
+    let object_id = generate_object_url(data.domain(), &id)?;
+    let post = DbPost::read_from_id(object_id, &data)
+        .await?
+        .ok_or_else(|| anyhow!("Object not found"))?;
+    Ok(FederationJson(WithContext::new_default(Replies::new(
+        &post, &data,
+    )?)))
+}
+
 async fn http_post_user_inbox(
     data: Data<DatabaseHandle>,
     activity_data: ActivityData,
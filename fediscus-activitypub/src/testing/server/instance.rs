@@ -1,4 +1,5 @@
 use crate::testing::server::{
+    activities::Flag,
     error::Error,
     http,
     objects::{DbPost, DbUser},
@@ -38,6 +39,7 @@ pub async fn new_instance(
         system_user: system_user.clone(),
         users: Mutex::new(vec![local_user]),
         posts: Mutex::new(vec![]),
+        reports: Mutex::new(vec![]),
     });
     let config = FederationConfig::builder()
         .domain(hostname)
@@ -57,6 +59,8 @@ pub struct Database {
     pub system_user: DbUser,
     pub users: Mutex<Vec<DbUser>>,
     pub posts: Mutex<Vec<DbPost>>,
+    /// Flag activities received by the local user
+    pub reports: Mutex<Vec<Flag>>,
 }
 
 /// Use this to store your federation blocklist, or a database connection needed to retrieve it.
@@ -98,6 +102,22 @@ impl Database {
             .clone()
     }
 
+    /// Replaces the key of the first local user with a different one, as if the user
+    /// rotated their key.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`Database::local_user`].
+    pub fn rotate_local_user_key(&self) {
+        let mut lock = self
+            .users
+            .lock()
+            .expect("Failed to acquire users lock - mutex poisoned");
+        lock.first_mut()
+            .expect("No test users found - did you forget to create one?")
+            .set_key(SYSTEM_USER_RSA_PRIVATE_KEY, SYSTEM_USER_RSA_PUBLIC_KEY);
+    }
+
     pub fn read_user(&self, name: &str) -> Result<DbUser, Error> {
         let db_user = self.local_user();
         if name == db_user.name {
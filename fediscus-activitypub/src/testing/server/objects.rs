@@ -2,4 +2,4 @@ mod person;
 mod post;
 
 pub use person::{DbUser, Person, PersonAcceptedActivities};
-pub use post::{DbPost, Note};
+pub use post::{DbPost, Note, Replies};
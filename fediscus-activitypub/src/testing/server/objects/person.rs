@@ -1,5 +1,5 @@
 use crate::testing::server::{
-    activities::{Accept, CreatePost, Follow, Undo},
+    activities::{Accept, CreatePost, DeleteUser, Flag, Follow, Undo, UpdatePost, UpdateUser},
     error::Error,
     instance::DatabaseHandle,
     objects::post::DbPost,
@@ -10,7 +10,7 @@ use activitypub_federation::{
     activity_sending::SendActivityTask,
     config::Data,
     fetch::{object_id::ObjectId, webfinger::webfinger_resolve_actor},
-    kinds::actor::PersonType,
+    kinds::{actor::PersonType, public},
     protocol::{context::WithContext, public_key::PublicKey, verification::verify_domains_match},
     traits::{ActivityHandler, Actor, Object},
 };
@@ -24,6 +24,8 @@ pub struct DbUser {
     pub name: String,
     pub ap_id: ObjectId<DbUser>,
     pub inbox: Url,
+    // exists only for remote users which advertise one
+    shared_inbox: Option<Url>,
     // exists for all users (necessary to verify http signatures)
     public_key: String,
     // exists only for local users
@@ -42,6 +44,7 @@ pub enum PersonAcceptedActivities {
     Accept(Accept),
     CreateNote(CreatePost),
     Undo(Undo),
+    Flag(Flag),
 }
 
 impl DbUser {
@@ -57,6 +60,7 @@ impl DbUser {
             name,
             ap_id,
             inbox,
+            shared_inbox: None,
             public_key: pub_key.to_string(),
             private_key: Some(private_key.to_string()),
             last_refreshed_at: Utc::now(),
@@ -74,9 +78,17 @@ pub struct Person {
     preferred_username: String,
     id: ObjectId<DbUser>,
     inbox: Url,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    endpoints: Option<Endpoints>,
     public_key: PublicKey,
 }
 
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoints {
+    shared_inbox: Option<Url>,
+}
+
 impl DbUser {
     pub fn followers(&self) -> &Vec<Url> {
         &self.followers
@@ -114,6 +126,7 @@ impl DbUser {
     }
 
     pub async fn post(&self, post: DbPost, data: &Data<DatabaseHandle>) -> Result<(), Error> {
+        data.posts.lock().unwrap().push(post.clone());
         let id = generate_object_id(data.domain())?;
         let create = CreatePost::new(post.into_json(data).await?, id.clone());
         let mut inboxes = vec![];
@@ -125,6 +138,79 @@ impl DbUser {
         Ok(())
     }
 
+    pub async fn update(&self, post: DbPost, data: &Data<DatabaseHandle>) -> Result<(), Error> {
+        data.posts
+            .lock()
+            .unwrap()
+            .iter_mut()
+            .filter(|p| p.ap_id == post.ap_id)
+            .for_each(|p| p.text = post.text.clone());
+        let id = generate_object_id(data.domain())?;
+        let update = UpdatePost::new(post.into_json(data).await?, id.clone());
+        let mut inboxes = vec![];
+        for f in self.followers.clone() {
+            let user: DbUser = ObjectId::from(f).dereference(data).await?;
+            inboxes.push(user.shared_inbox_or_inbox());
+        }
+        self.send(update, inboxes, true, data).await?;
+        Ok(())
+    }
+
+    /// Replaces the key of the user, as if they rotated it
+    pub(crate) fn set_key(&mut self, private_key: &str, public_key: &str) {
+        self.private_key = Some(private_key.to_string());
+        self.public_key = public_key.to_string();
+    }
+
+    /// Lets all the followers know about changes to the user's profile
+    pub async fn update_profile(&self, data: &Data<DatabaseHandle>) -> Result<(), Error> {
+        let id = generate_object_id(data.domain())?;
+        let update = UpdateUser::new(
+            self.ap_id.clone(),
+            vec![public(), self.followers_url()?],
+            self.clone().into_json(data).await?,
+            id,
+        );
+        let mut inboxes = vec![];
+        for f in self.followers.clone() {
+            let user: DbUser = ObjectId::from(f).dereference(data).await?;
+            inboxes.push(user.shared_inbox_or_inbox());
+        }
+        self.send(update, inboxes, false, data).await?;
+        Ok(())
+    }
+
+    /// Deletes the account and lets all the followers know about it
+    pub async fn delete(&self, data: &Data<DatabaseHandle>) -> Result<(), Error> {
+        let id = generate_object_id(data.domain())?;
+        let delete = DeleteUser::new(
+            self.ap_id.clone(),
+            vec![public(), self.followers_url()?],
+            id,
+        );
+        let mut inboxes = vec![];
+        for f in self.followers.clone() {
+            let user: DbUser = ObjectId::from(f).dereference(data).await?;
+            inboxes.push(user.shared_inbox_or_inbox());
+        }
+        self.send(delete, inboxes, false, data).await?;
+        Ok(())
+    }
+
+    /// Reports the objects to the moderators of the instance behind the inbox
+    pub async fn report(
+        &self,
+        objects: Vec<Url>,
+        content: Option<String>,
+        inbox: Url,
+        data: &Data<DatabaseHandle>,
+    ) -> Result<Url, Error> {
+        let id = generate_object_id(data.domain())?;
+        let flag = Flag::new(self.ap_id.clone(), objects, content, id.clone());
+        self.send(flag, vec![inbox], false, data).await?;
+        Ok(id)
+    }
+
     pub(crate) async fn send<Activity>(
         &self,
         activity: Activity,
@@ -178,6 +264,7 @@ impl Object for DbUser {
             kind: Default::default(),
             id: self.ap_id.clone(),
             inbox: self.inbox.clone(),
+            endpoints: None,
             public_key: self.public_key(),
         })
     }
@@ -196,6 +283,7 @@ impl Object for DbUser {
             name: json.preferred_username,
             ap_id: json.id,
             inbox: json.inbox,
+            shared_inbox: json.endpoints.and_then(|endpoints| endpoints.shared_inbox),
             public_key: json.public_key.public_key_pem,
             private_key: None,
             last_refreshed_at: Utc::now(),
@@ -224,4 +312,8 @@ impl Actor for DbUser {
     fn inbox(&self) -> Url {
         self.inbox.clone()
     }
+
+    fn shared_inbox(&self) -> Option<Url> {
+        self.shared_inbox.clone()
+    }
 }
@@ -4,7 +4,11 @@ use crate::testing::server::{
 use activitypub_federation::{
     config::Data,
     fetch::object_id::ObjectId,
-    kinds::{object::NoteType, public},
+    kinds::{
+        collection::{CollectionPageType, CollectionType},
+        object::NoteType,
+        public,
+    },
     protocol::{helpers::deserialize_one_or_many, verification::verify_domains_match},
     traits::Object,
 };
@@ -46,6 +50,10 @@ impl DbPost {
             in_reply_to: Some(in_reply_to),
         })
     }
+
+    pub fn replies_url(&self) -> Result<Url, Error> {
+        Ok(Url::parse(&format!("{}/replies", self.ap_id.inner()))?)
+    }
 }
 
 #[derive(Deserialize, Serialize, Debug)]
@@ -59,6 +67,49 @@ pub struct Note {
     pub(crate) to: Vec<Url>,
     content: String,
     in_reply_to: Option<ObjectId<DbPost>>,
+    replies: Option<Url>,
+}
+
+/// The `replies` collection of a post, with all replies in the first page
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Replies {
+    #[serde(rename = "type")]
+    kind: CollectionType,
+    id: Url,
+    first: RepliesPage,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct RepliesPage {
+    #[serde(rename = "type")]
+    kind: CollectionPageType,
+    next: Option<Url>,
+    items: Vec<Url>,
+}
+
+impl Replies {
+    /// Collects the replies of the given post among all known posts
+    pub fn new(post: &DbPost, data: &Data<DatabaseHandle>) -> Result<Replies, Error> {
+        let items = data
+            .posts
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|p| p.in_reply_to.as_ref() == Some(&post.ap_id))
+            .map(|p| p.ap_id.inner().clone())
+            .collect();
+        Ok(Replies {
+            kind: Default::default(),
+            id: post.replies_url()?,
+            first: RepliesPage {
+                kind: Default::default(),
+                next: None,
+                items,
+            },
+        })
+    }
 }
 
 #[async_trait::async_trait]
@@ -81,6 +132,7 @@ impl Object for DbPost {
 
     async fn into_json(self, data: &Data<Self::DataType>) -> Result<Self::Kind, Self::Error> {
         let creator = self.creator.dereference_local(data).await?;
+        let replies = self.replies_url()?;
         Ok(Note {
             kind: Default::default(),
             id: self.ap_id,
@@ -88,6 +140,7 @@ impl Object for DbPost {
             to: vec![public(), creator.followers_url()?],
             content: self.text,
             in_reply_to: self.in_reply_to,
+            replies: Some(replies),
         })
     }
 
@@ -9,5 +9,10 @@ pub fn generate_object_id(domain: &str) -> Result<Url, ParseError> {
         .take(7)
         .map(char::from)
         .collect();
+    generate_object_url(domain, &id)
+}
+
+/// Returns the url of the object with the given id
+pub fn generate_object_url(domain: &str, id: &str) -> Result<Url, ParseError> {
     Url::parse(&format!("http://{}/objects/{}", domain, id))
 }
@@ -0,0 +1,225 @@
+use fediscus_activitypub::testing::server::{listen, new_instance, DbPost};
+use serial_test::serial;
+use tracing::info;
+use url::Url;
+
+mod common;
+
+use common::FediscusServer;
+
+#[tokio::test]
+#[serial]
+async fn test_deleted_account_is_purged() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+    let fediscus_user = fediscus
+        .service
+        .storage()
+        .get_local_account()
+        .await
+        .expect("Failed to retrieve local account");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // The test users follows fediscus
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // And creates a new post that contains a link and the #fediscus tag
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post, &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .post_count()
+            .await
+            .expect("Failed to count posts"),
+        1
+    );
+
+    let test_user = fediscus
+        .service
+        .storage()
+        .account_by_uri(&Url::parse("http://localhost:8087/testuser").unwrap().into())
+        .await
+        .expect("Failed to retrieve users")
+        .expect("Test user does not exist in local database");
+
+    // The test user deletes their account
+    test_server
+        .local_user()
+        .delete(&test_server.to_request_data())
+        .await
+        .expect("Failed to delete account");
+
+    // Fediscus no longer knows about the account, its notes or follows
+    assert!(fediscus
+        .service
+        .storage()
+        .account_by_id(test_user.id)
+        .await
+        .expect("Failed to retrieve users")
+        .is_none());
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .post_count()
+            .await
+            .expect("Failed to count posts"),
+        0
+    );
+    assert!(fediscus
+        .service
+        .storage()
+        .follow_by_ids(test_user.id, fediscus_user.id)
+        .await
+        .expect("Failed to retrieve follow relation")
+        .is_none());
+    assert!(fediscus
+        .service
+        .storage()
+        .follow_by_ids(fediscus_user.id, test_user.id)
+        .await
+        .expect("Failed to retrieve back-follow relation")
+        .is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_rotated_key_is_picked_up() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // The test users follows fediscus
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let test_user_uri = Url::parse("http://localhost:8087/testuser").unwrap().into();
+    let old_key = fediscus
+        .service
+        .storage()
+        .account_by_uri(&test_user_uri)
+        .await
+        .expect("Failed to retrieve users")
+        .expect("Test user does not exist in local database")
+        .public_key;
+
+    // The test user rotates their key and lets their followers know, signing the
+    // Update with the new key that Fediscus doesn't know yet
+    test_server.rotate_local_user_key();
+    test_server
+        .local_user()
+        .update_profile(&test_server.to_request_data())
+        .await
+        .expect("Failed to update profile");
+
+    let test_user = fediscus
+        .service
+        .storage()
+        .account_by_uri(&test_user_uri)
+        .await
+        .expect("Failed to retrieve users")
+        .expect("Test user does not exist in local database");
+    assert_ne!(test_user.public_key, old_key);
+
+    // Activities signed with the new key are accepted
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post, &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .post_count()
+            .await
+            .expect("Failed to count posts"),
+        1
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_blocked_actor_is_rejected() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let test_user_uri = Url::parse("http://localhost:8087/testuser").unwrap();
+    fediscus
+        .service
+        .storage()
+        .new_actor_block(&test_user_uri.clone().into(), Some("spam"))
+        .await
+        .expect("Failed to block actor");
+
+    // The follow is rejected at the inbox
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect_err("Follow of a blocked actor was accepted");
+
+    assert!(fediscus
+        .service
+        .storage()
+        .account_by_uri(&test_user_uri.into())
+        .await
+        .expect("Failed to retrieve account")
+        .is_none());
+}
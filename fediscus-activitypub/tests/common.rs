@@ -2,8 +2,10 @@ use std::sync::Arc;
 
 use activitypub_federation::config::FederationConfig;
 use anyhow::Error;
+use fediscus_activitypub::filters::FilterChain;
 use fediscus_activitypub::testing::MemoryStorage;
 use fediscus_activitypub::ActivityPubService;
+use fediscus_activitypub::BlocklistVerifier;
 use fediscus_activitypub::Config;
 use fediscus_activitypub::FederationData;
 use fediscus_activitypub::HttpServer;
@@ -12,6 +14,7 @@ use tokio::task::AbortHandle;
 
 pub struct FediscusServer {
     pub service: Arc<Box<dyn ActivityPubService + Send + Sync + 'static>>,
+    pub federation: FederationConfig<FederationData>,
     task: AbortHandle,
 }
 
@@ -32,22 +35,25 @@ impl FediscusServer {
         let federation_data = FederationData {
             config: config.clone(),
             service: Arc::clone(&service),
+            filters: Arc::new(FilterChain::from_config(&config.filters)?),
         };
 
         let federation = FederationConfig::builder()
             .domain(config.fediverse_user.host.clone())
             .app_data(federation_data)
+            .url_verifier(Box::new(BlocklistVerifier::new(Arc::clone(&service))))
             .debug(true)
             .allow_http_urls(true)
             .domain("localhost:8086")
             .build()
             .await?;
 
-        let http_server = HttpServer::new(&config, federation).await?;
+        let http_server = HttpServer::new(&config, federation.clone()).await?;
         let task = tokio::spawn(async { http_server.run().await });
 
         Ok(Self {
             service,
+            federation,
             task: task.abort_handle(),
         })
     }
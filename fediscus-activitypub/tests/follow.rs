@@ -1,3 +1,4 @@
+use activitypub_federation::fetch::fetch_object_http;
 use fediscus_activitypub::testing::server::{listen, new_instance};
 use serial_test::serial;
 use tracing::info;
@@ -164,3 +165,198 @@ async fn test_handle_unfollow() {
     // The test user is no longer being followed by Fediscus
     assert!(test_server.local_user().followers.is_empty());
 }
+
+#[tokio::test]
+#[serial]
+async fn test_followers_collection() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let _fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // The test users follows fediscus
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let followers_url = Url::parse("http://localhost:8086/users/fediscus/followers").unwrap();
+    let followers =
+        fetch_object_http::<_, serde_json::Value>(&followers_url, &test_server.to_request_data())
+            .await
+            .expect("Failed to fetch followers")
+            .object;
+    assert_eq!(followers["type"], "OrderedCollection");
+    assert_eq!(followers["totalItems"], 1);
+    let first = Url::parse(followers["first"].as_str().expect("No first page")).unwrap();
+
+    let page = fetch_object_http::<_, serde_json::Value>(&first, &test_server.to_request_data())
+        .await
+        .expect("Failed to fetch followers page")
+        .object;
+    assert_eq!(page["type"], "OrderedCollectionPage");
+    assert_eq!(page["partOf"], followers_url.as_str());
+    assert_eq!(
+        page["orderedItems"],
+        serde_json::json!(["http://localhost:8087/testuser"])
+    );
+    assert!(page.get("next").is_none());
+
+    // Pages beyond any possible offset are refused rather than overflowing
+    let mut far = followers_url.clone();
+    far.query_pairs_mut()
+        .append_pair("page", &usize::MAX.to_string());
+    assert!(
+        fetch_object_http::<_, serde_json::Value>(&far, &test_server.to_request_data())
+            .await
+            .is_err()
+    );
+
+    // Fediscus follows the test user back
+    let following = fetch_object_http::<_, serde_json::Value>(
+        &Url::parse("http://localhost:8086/users/fediscus/following").unwrap(),
+        &test_server.to_request_data(),
+    )
+    .await
+    .expect("Failed to fetch following")
+    .object;
+    assert_eq!(following["totalItems"], 1);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_shared_inbox() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let actor = fetch_object_http::<_, serde_json::Value>(
+        &Url::parse("http://localhost:8086/users/fediscus").unwrap(),
+        &test_server.to_request_data(),
+    )
+    .await
+    .expect("Failed to fetch Fediscus actor")
+    .object;
+    assert_eq!(
+        actor["endpoints"]["sharedInbox"],
+        "http://localhost:8086/inbox"
+    );
+
+    // The test server prefers the shared inbox, so the follow is delivered there
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let test_user = fediscus
+        .service
+        .storage()
+        .account_by_uri(&Url::parse("http://localhost:8087/testuser").unwrap().into())
+        .await
+        .expect("Failed to retrieve users")
+        .expect("Test user does not exist in local database");
+    let fediscus_user = fediscus
+        .service
+        .storage()
+        .get_local_account()
+        .await
+        .expect("Failed to retrieve local account");
+    fediscus
+        .service
+        .storage()
+        .follow_by_ids(test_user.id, fediscus_user.id)
+        .await
+        .expect("Failed to retrieve follow relation")
+        .expect("Follow relation from test user to fediscus does not exist in local database");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_outbox() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // Fediscus accepts the follow of the test user and follows it back
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // The follow and its acceptance are not public, so they're not listed
+    let outbox_url = Url::parse("http://localhost:8086/users/fediscus/outbox").unwrap();
+    let outbox =
+        fetch_object_http::<_, serde_json::Value>(&outbox_url, &test_server.to_request_data())
+            .await
+            .expect("Failed to fetch outbox")
+            .object;
+    assert_eq!(outbox["type"], "OrderedCollection");
+    assert_eq!(outbox["totalItems"], 0);
+
+    fediscus
+        .service
+        .announce_blog_post(
+            Url::parse("https://example.com/blog-post").unwrap(),
+            "My new post".to_string(),
+            &fediscus.federation.to_request_data(),
+        )
+        .await
+        .expect("Failed to announce blog post");
+
+    let outbox =
+        fetch_object_http::<_, serde_json::Value>(&outbox_url, &test_server.to_request_data())
+            .await
+            .expect("Failed to fetch outbox")
+            .object;
+    assert_eq!(outbox["totalItems"], 1);
+    let first = Url::parse(outbox["first"].as_str().expect("No first page")).unwrap();
+
+    let page = fetch_object_http::<_, serde_json::Value>(&first, &test_server.to_request_data())
+        .await
+        .expect("Failed to fetch outbox page")
+        .object;
+    assert_eq!(page["type"], "OrderedCollectionPage");
+    assert_eq!(page["partOf"], outbox_url.as_str());
+    let types: Vec<_> = page["orderedItems"]
+        .as_array()
+        .expect("No items")
+        .iter()
+        .map(|activity| activity["type"].as_str().unwrap())
+        .collect();
+    assert_eq!(types, vec!["Create"]);
+}
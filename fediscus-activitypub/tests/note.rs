@@ -1,4 +1,5 @@
 use fediscus_activitypub::testing::server::{listen, new_instance, DbPost};
+use fediscus_activitypub::{ModerationPolicy, ModerationState};
 use serial_test::serial;
 use tracing::info;
 
@@ -219,6 +220,10 @@ async fn test_followed_user_posts_note_with_url_and_tag() {
     assert_eq!(post.blog_id, blog.id);
     assert_eq!(post.root_id, None);
     assert_eq!(post.reply_to_id, None);
+    assert_eq!(
+        post.content,
+        "My new post! https://example.com/blog-post #fediscus"
+    );
 }
 
 #[tokio::test]
@@ -323,4 +328,731 @@ async fn test_followed_user_posts_reply_to_note() {
     assert_eq!(reply.blog_id, blog.id);
     assert_eq!(reply.reply_to_id, Some(post.id));
     assert_eq!(reply.root_id, Some(post.id));
+    assert_eq!(reply.content, "Wow, this is a great post!");
+}
+
+#[tokio::test]
+#[serial]
+async fn test_followed_user_edits_note() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // The test users follows fediscus
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // Creates a new post that contains a link and the #fediscus tag
+    let mut post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // And then edits it
+    post.text = "My edited post! https://example.com/blog-post #fediscus".to_string();
+    test_server
+        .local_user()
+        .update(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to update note");
+
+    let stored = fediscus
+        .service
+        .storage()
+        .post_by_uri(&post.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Post not found");
+    assert_eq!(
+        stored.content,
+        "My edited post! https://example.com/blog-post #fediscus"
+    );
+
+    // Removing the #fediscus tag removes the thread
+    post.text = "My edited post! https://example.com/blog-post".to_string();
+    test_server
+        .local_user()
+        .update(post, &test_server.to_request_data())
+        .await
+        .expect("Failed to update note");
+
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .post_count()
+            .await
+            .expect("Failed to count posts"),
+        0
+    );
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reply_to_unknown_note_fetches_ancestors() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let test_server2 = new_instance("localhost:8088", "testuser2".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server2).expect("Failed to start test server");
+    info!("Test server 2 listening");
+
+    // Only the second user follows fediscus, so posts of the first user are never delivered
+    test_server2
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server2.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // The first user posts a #fediscus note and replies to it
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    let reply = DbPost::new_reply(
+        "Some more thoughts".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .post_count()
+            .await
+            .expect("Failed to count posts"),
+        0
+    );
+
+    // The second user replies to the reply, which makes fediscus fetch the whole chain
+    let reply2 = DbPost::new_reply(
+        "I agree!".to_string(),
+        test_server2.local_user().ap_id.clone(),
+        reply.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server2
+        .local_user()
+        .post(reply2.clone(), &test_server2.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let storage = fediscus.service.storage();
+    assert_eq!(
+        storage.post_count().await.expect("Failed to count posts"),
+        3
+    );
+    let root = storage
+        .post_by_uri(&post.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Root not found");
+    let reply = storage
+        .post_by_uri(&reply.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    let reply2 = storage
+        .post_by_uri(&reply2.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Second reply not found");
+    let blog = storage
+        .blog_by_url(&Url::parse("https://example.com/blog-post").unwrap())
+        .await
+        .expect("Failed to get blog")
+        .expect("Blog not found");
+    assert_eq!(root.reply_to_id, None);
+    assert_eq!(root.blog_id, blog.id);
+    assert_eq!(reply.reply_to_id, Some(root.id));
+    assert_eq!(reply.root_id, Some(root.id));
+    assert_eq!(reply.blog_id, blog.id);
+    assert_eq!(reply2.reply_to_id, Some(reply.id));
+    assert_eq!(reply2.root_id, Some(root.id));
+    assert_eq!(reply2.blog_id, blog.id);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_blog_post_announcement() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    // The test users follows fediscus
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // Fediscus announces a new blog post
+    let url = Url::parse("https://example.com/blog-post").unwrap();
+    let note = fediscus
+        .service
+        .announce_blog_post(
+            url.clone(),
+            "My <new> post".to_string(),
+            &fediscus.federation.to_request_data(),
+        )
+        .await
+        .expect("Failed to announce blog post");
+
+    // The announcement is the root of the blog post's thread
+    let blog = fediscus
+        .service
+        .storage()
+        .blog_by_url(&url)
+        .await
+        .expect("Failed to get blog")
+        .expect("Blog not found");
+    assert_eq!(note.blog_id, blog.id);
+    assert_eq!(note.root_id, None);
+
+    // And it has been delivered to the follower
+    let delivered = test_server
+        .posts
+        .lock()
+        .unwrap()
+        .iter()
+        .find(|p| p.ap_id.inner() == note.uri.as_url())
+        .cloned()
+        .expect("Announcement was not delivered");
+    assert!(delivered.text.contains("https://example.com/blog-post"));
+    assert!(delivered.text.contains("My &lt;new&gt; post"));
+    assert!(delivered.text.contains("#fediscus"));
+
+    // Replies to the announcement are collected like replies to any other root
+    let reply = DbPost::new_reply(
+        "Wow, this is a great post!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        delivered.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply, &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let reply = fediscus
+        .service
+        .storage()
+        .post_by_id(2.into())
+        .await
+        .expect("Failed to get post")
+        .expect("Post reply not found");
+    assert_eq!(reply.reply_to_id, Some(note.id));
+    assert_eq!(reply.root_id, Some(note.id));
+    assert_eq!(reply.blog_id, blog.id);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reply_is_held_for_moderation() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let test_server2 = new_instance("localhost:8088", "testuser2".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server2).expect("Failed to start test server");
+    info!("Test server 2 listening");
+
+    for server in [&test_server, &test_server2] {
+        server
+            .local_user()
+            .follow("fediscus@localhost:8086", &server.to_request_data())
+            .await
+            .expect("Failed to follow Fediscus");
+    }
+
+    // Only comments of accounts we already know are published right away
+    let url = Url::parse("https://example.com/blog-post").unwrap();
+    fediscus
+        .service
+        .set_moderation_policy(&url, ModerationPolicy::ApproveKnown)
+        .await
+        .expect("Failed to set moderation policy");
+
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // A newcomer's reply is held...
+    let reply = DbPost::new_reply(
+        "Buy cheap watches!".to_string(),
+        test_server2.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server2
+        .local_user()
+        .post(reply.clone(), &test_server2.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // ...while the author of the approved root note is known already
+    let answer = DbPost::new_reply(
+        "Thanks for reading!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(answer.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let storage = fediscus.service.storage();
+    let held = storage
+        .post_by_uri(&reply.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    assert_eq!(held.moderation_state, ModerationState::Pending);
+    let approved = storage
+        .post_by_uri(&answer.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    assert_eq!(approved.moderation_state, ModerationState::Approved);
+
+    let queue = storage
+        .posts_by_moderation_state(ModerationState::Pending)
+        .await
+        .expect("Failed to get moderation queue");
+    assert_eq!(queue.len(), 1);
+    assert_eq!(queue[0].id, held.id);
+
+    // The blog owner rejects the held reply
+    fediscus
+        .service
+        .moderate_note(held.uri.clone(), ModerationState::Rejected)
+        .await
+        .expect("Failed to moderate note");
+    let rejected = storage
+        .post_by_id(held.id)
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    assert_eq!(rejected.moderation_state, ModerationState::Rejected);
+    assert!(storage
+        .posts_by_moderation_state(ModerationState::Pending)
+        .await
+        .expect("Failed to get moderation queue")
+        .is_empty());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reply_is_rejected_by_filters() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // The test configuration rejects comments mentioning casinos
+    let spam = DbPost::new_reply(
+        "Best online Casino bonuses!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(spam.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let reply = DbPost::new_reply(
+        "Great read, thanks!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let storage = fediscus.service.storage();
+    assert!(storage
+        .post_by_uri(&spam.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .is_none());
+    let stored = storage
+        .post_by_uri(&reply.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    assert_eq!(stored.moderation_state, ModerationState::Approved);
+}
+
+#[tokio::test]
+#[serial]
+async fn test_edited_reply_is_rejected_by_filters() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let mut reply = DbPost::new_reply(
+        "Great read, thanks!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // Editing the reply into spam doesn't get it past the filters
+    reply.text = "Best online Casino bonuses!".to_string();
+    test_server
+        .local_user()
+        .update(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to update note");
+
+    let stored = fediscus
+        .service
+        .storage()
+        .post_by_uri(&reply.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Reply not found");
+    assert!(stored.is_deleted());
+    assert!(!stored.content.contains("Casino"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_note_is_rejected_by_filters() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // Top-level notes go through the filters as well as the replies
+    let spam = DbPost::new(
+        "Casino bonuses! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(spam.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    assert!(fediscus
+        .service
+        .storage()
+        .post_by_uri(&spam.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reply_to_unreachable_parent() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // The parent is never published, so fetching it fails
+    let missing = DbPost::new(
+        "Lost note".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    let reply = DbPost::new_reply(
+        "Replying here too https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+        missing.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // The reply is still processed, as a top-level note about the blog post
+    let storage = fediscus.service.storage();
+    let stored = storage
+        .post_by_uri(&reply.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .expect("Note not found");
+    assert!(stored.reply_to_id.is_none());
+    assert!(storage
+        .post_by_uri(&missing.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .is_none());
+}
+
+#[tokio::test]
+#[serial]
+async fn test_reply_under_rejected_ancestor() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let test_server2 = new_instance("localhost:8088", "testuser2".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server2).expect("Failed to start test server");
+    info!("Test server 2 listening");
+
+    // Only the second user follows fediscus, so posts of the first user are never delivered
+    test_server2
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server2.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    // The first user posts a #fediscus note, a reply the filters reject and a reply to that
+    let post = DbPost::new(
+        "My new post! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    let spam = DbPost::new_reply(
+        "Best online Casino bonuses!".to_string(),
+        test_server.local_user().ap_id.clone(),
+        post.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(spam.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+    let reply = DbPost::new_reply(
+        "Sign up here".to_string(),
+        test_server.local_user().ap_id.clone(),
+        spam.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(reply.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // The second user replies at the bottom of the chain
+    let reply2 = DbPost::new_reply(
+        "Is this legit?".to_string(),
+        test_server2.local_user().ap_id.clone(),
+        reply.ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server2
+        .local_user()
+        .post(reply2.clone(), &test_server2.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    // Nothing below the rejected note makes it into the thread
+    let storage = fediscus.service.storage();
+    assert!(storage
+        .post_by_uri(&post.ap_id.inner().clone().into())
+        .await
+        .expect("Failed to get post")
+        .is_some());
+    for note in [&spam, &reply, &reply2] {
+        assert!(storage
+            .post_by_uri(&note.ap_id.inner().clone().into())
+            .await
+            .expect("Failed to get post")
+            .is_none());
+    }
 }
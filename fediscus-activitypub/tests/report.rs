@@ -0,0 +1,136 @@
+use fediscus_activitypub::testing::server::{listen, new_instance, DbPost};
+use serial_test::serial;
+use tracing::info;
+use url::Url;
+
+mod common;
+
+use common::FediscusServer;
+
+#[tokio::test]
+#[serial]
+async fn test_report_is_delivered() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    test_server
+        .local_user()
+        .follow("fediscus@localhost:8086", &test_server.to_request_data())
+        .await
+        .expect("Failed to follow Fediscus");
+
+    let post = DbPost::new(
+        "Buy now! https://example.com/blog-post #fediscus".to_string(),
+        test_server.local_user().ap_id.clone(),
+    )
+    .expect("Failed to create post");
+    test_server
+        .local_user()
+        .post(post.clone(), &test_server.to_request_data())
+        .await
+        .expect("Failed to post note");
+
+    let reported = fediscus
+        .service
+        .report_notes(
+            vec![post.ap_id.inner().clone().into()],
+            Some("Spam".to_string()),
+            &fediscus.federation.to_request_data(),
+        )
+        .await
+        .expect("Failed to report note");
+    assert_eq!(reported, 1);
+
+    // The instance of the author receives the report of the author and the note
+    let reports = test_server.reports.lock().unwrap().clone();
+    assert_eq!(reports.len(), 1);
+    assert_eq!(
+        reports[0].actor.inner(),
+        &Url::parse("http://localhost:8086/users/fediscus").unwrap()
+    );
+    assert_eq!(
+        reports[0].object,
+        vec![
+            test_server.local_user().ap_id.inner().clone(),
+            post.ap_id.inner().clone()
+        ]
+    );
+    assert_eq!(reports[0].content.as_deref(), Some("Spam"));
+}
+
+#[tokio::test]
+#[serial]
+async fn test_incoming_report_is_stored() {
+    tracing_subscriber::fmt()
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+
+    let fediscus = FediscusServer::new()
+        .await
+        .expect("Failed to start Fediscus server");
+    info!("Fediscus server started");
+
+    let test_server = new_instance("localhost:8087", "testuser".to_string())
+        .await
+        .expect("Failed to start test server");
+    listen(&test_server).expect("Failed to start test server");
+    info!("Test server listening");
+
+    let inbox = Url::parse("http://localhost:8086/inbox").unwrap();
+    let fediscus_user = Url::parse("http://localhost:8086/users/fediscus").unwrap();
+    let id = test_server
+        .local_user()
+        .report(
+            vec![fediscus_user.clone()],
+            Some("Rude".to_string()),
+            inbox.clone(),
+            &test_server.to_request_data(),
+        )
+        .await
+        .expect("Failed to send report");
+
+    let reports = fediscus
+        .service
+        .storage()
+        .unresolved_reports()
+        .await
+        .expect("Failed to list reports");
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].uri.as_url(), &id);
+    assert_eq!(reports[0].content.as_deref(), Some("Rude"));
+    assert_eq!(reports[0].objects.0, vec![fediscus_user]);
+
+    // Reports that don't concern any of our objects are refused
+    test_server
+        .local_user()
+        .report(
+            vec![Url::parse("https://elsewhere.example/notes/1").unwrap()],
+            None,
+            inbox,
+            &test_server.to_request_data(),
+        )
+        .await
+        .expect_err("Report of foreign objects was accepted");
+    assert_eq!(
+        fediscus
+            .service
+            .storage()
+            .unresolved_reports()
+            .await
+            .expect("Failed to list reports")
+            .len(),
+        1
+    );
+}
@@ -7,12 +7,44 @@ use thiserror::Error;
 pub enum ConfigError {
     #[error("Error in HTTP server configuration: {0}")]
     HttpServerConfigurationError(HttpServerConfigError),
+    #[error("Pool size must be greater than 0")]
+    InvalidPoolSize,
+}
+
+const fn default_pool_size() -> u32 {
+    10
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+/// The database configuration
+pub struct Database {
+    /// The URL to connect to the database, must point to the same database
+    /// as the one used by fediscus-activitypub
+    pub url: String,
+
+    /// The maximum number of connections to keep in the pool
+    /// Defaults to 10 if not specified
+    #[serde(default = "default_pool_size")]
+    pub pool_size: u32,
+}
+
+impl Database {
+    /// Validates the database configuration
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.pool_size == 0 {
+            return Err(ConfigError::InvalidPoolSize);
+        }
+
+        Ok(())
+    }
 }
 
 #[derive(Clone, Debug, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct Config {
     pub http_server: HttpServerConfig,
+    pub database: Database,
 }
 
 impl Config {
@@ -30,6 +62,9 @@ impl Config {
         cfg.http_server.validate().map_err(|e| {
             config::ConfigError::Message(ConfigError::HttpServerConfigurationError(e).to_string())
         })?;
+        cfg.database
+            .validate()
+            .map_err(|e| config::ConfigError::Message(e.to_string()))?;
 
         Ok(cfg)
     }
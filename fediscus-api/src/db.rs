@@ -0,0 +1,775 @@
+//! Access to the database shared with fediscus-activitypub.
+
+use chrono::NaiveDateTime;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use sqlx::{QueryBuilder, SqlitePool};
+
+#[derive(Debug, Clone)]
+pub struct Blog {
+    pub id: i64,
+    pub url: String,
+}
+
+/// A single note together with its author
+#[derive(Debug, Clone)]
+pub struct CommentRow {
+    pub id: i64,
+    pub uri: String,
+    pub url: Option<String>,
+    pub reply_to_id: Option<i64>,
+    pub root_id: Option<i64>,
+    pub content: String,
+    pub summary: Option<String>,
+    pub language: Option<String>,
+    pub published: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub deleted_at: Option<NaiveDateTime>,
+    pub likes: i64,
+    pub reposts: i64,
+    pub author_uri: String,
+    pub author_username: String,
+    pub author_host: String,
+    pub author_display_name: Option<String>,
+    pub author_avatar_url: Option<String>,
+    pub author_url: Option<String>,
+    pub author_bot: bool,
+    pub author_moved_to_uri: Option<String>,
+    pub author_moved_to_username: Option<String>,
+    pub author_moved_to_host: Option<String>,
+    pub author_moved_to_url: Option<String>,
+}
+
+/// Aggregated counts for a single blog post
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct CountsRow {
+    pub url: String,
+    pub replies: i64,
+    pub likes: i64,
+    pub reposts: i64,
+}
+
+pub async fn blog_by_url(db: &SqlitePool, url: &str) -> Result<Option<Blog>, sqlx::Error> {
+    sqlx::query_as!(Blog, r#"SELECT id, url FROM blogs WHERE url = ?"#, url)
+        .fetch_optional(db)
+        .await
+}
+
+/// Returns all approved notes (the root notes as well as all replies) belonging to the given
+/// blog, ordered from the oldest to the newest. Notes held for moderation, rejected or hidden
+/// are left out.
+pub async fn comments_by_blog_id(
+    db: &SqlitePool,
+    blog_id: i64,
+) -> Result<Vec<CommentRow>, sqlx::Error> {
+    sqlx::query_as!(
+        CommentRow,
+        r#"SELECT
+            n.id,
+            n.uri,
+            n.url,
+            n.reply_to_id,
+            n.root_id,
+            n.content,
+            n.summary,
+            n.language,
+            n.published AS "published: _",
+            n.created_at AS "created_at: _",
+            n.updated_at AS "updated_at: _",
+            n.deleted_at AS "deleted_at: _",
+            n.likes,
+            n.reposts,
+            a.uri AS "author_uri!",
+            a.username AS "author_username!",
+            a.host AS "author_host!",
+            a.display_name AS "author_display_name?",
+            a.avatar_url AS "author_avatar_url?",
+            a.url AS "author_url?",
+            a.bot AS "author_bot!",
+            m.uri AS "author_moved_to_uri?",
+            m.username AS "author_moved_to_username?",
+            m.host AS "author_moved_to_host?",
+            m.url AS "author_moved_to_url?"
+        FROM notes n
+        JOIN accounts a ON a.id = n.account_id
+        LEFT JOIN accounts m ON m.id = a.moved_to_id
+        WHERE n.blog_id = ? AND n.moderation_state = 'approved'
+        ORDER BY COALESCE(n.published, n.created_at), n.id"#,
+        blog_id
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Returns the number of publicly shown replies that have not been deleted and the total number
+/// of likes and reposts of the publicly shown notes across the whole thread for each of the given
+/// blog URLs. URLs that are not known are not included in the result.
+pub async fn counts_by_blog_urls(
+    db: &SqlitePool,
+    urls: &[String],
+) -> Result<Vec<CountsRow>, sqlx::Error> {
+    if urls.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    // Like the comments themselves, replies to notes that are not shown publicly are left out
+    // together with them
+    let mut query = QueryBuilder::new(
+        r#"WITH RECURSIVE requested(id, url) AS (
+            SELECT id, url FROM blogs WHERE url IN ("#,
+    );
+    let mut separated = query.separated(", ");
+    for url in urls {
+        separated.push_bind(url);
+    }
+    separated.push_unseparated(
+        r#")
+        ),
+        visible(id) AS (
+            SELECT id FROM notes
+            WHERE blog_id IN (SELECT id FROM requested)
+                AND reply_to_id IS NULL
+                AND moderation_state = 'approved'
+            UNION ALL
+            SELECT n.id FROM notes n
+            JOIN visible v ON n.reply_to_id = v.id
+            WHERE n.moderation_state = 'approved'
+        )
+        SELECT
+            b.url AS url,
+            COUNT(CASE WHEN n.deleted_at IS NULL THEN n.root_id END) AS replies,
+            COALESCE(SUM(n.likes), 0) AS likes,
+            COALESCE(SUM(n.reposts), 0) AS reposts
+        FROM requested b
+        LEFT JOIN notes n ON n.blog_id = b.id AND n.id IN (SELECT id FROM visible)
+        GROUP BY b.id"#,
+    );
+
+    query.build_query_as::<CountsRow>().fetch_all(db).await
+}
+
+/// Whether a comment is shown publicly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ModerationState {
+    /// Held for moderation
+    Pending,
+    /// Shown publicly
+    Approved,
+    /// Never shown publicly
+    Rejected,
+    /// Approved before, but taken down later
+    Hidden,
+}
+
+/// How replies to a blog post are moderated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum ModerationPolicy {
+    /// Replies are shown right away
+    AutoApprove,
+    /// Replies of authors with an already approved comment are shown right away, others are
+    /// held for moderation
+    ApproveKnown,
+    /// All replies are held for moderation
+    HoldAll,
+}
+
+/// What a job does with its target
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema, sqlx::Type)]
+#[serde(rename_all = "snake_case")]
+#[sqlx(rename_all = "snake_case")]
+pub enum JobKind {
+    /// Imports replies to the blog post, the target is the URL of the blog post
+    Backfill,
+    /// Re-fetches a remote account, the target is the ActivityPub ID of the account
+    RefreshAccount,
+}
+
+/// Marks the admin token with the given hash as used and returns its name, or `None` if there
+/// is no such token.
+pub async fn use_admin_token(
+    db: &SqlitePool,
+    token_hash: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"UPDATE admin_tokens SET last_used_at = DATETIME('now')
+        WHERE token_hash = ?
+        RETURNING name"#,
+        token_hash
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn new_admin_token(
+    db: &SqlitePool,
+    name: &str,
+    token_hash: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query!(
+        r#"INSERT INTO admin_tokens (name, token_hash) VALUES (?, ?)"#,
+        name,
+        token_hash
+    )
+    .execute(db)
+    .await?;
+    Ok(())
+}
+
+/// Deletes the admin token with the given name, returns whether it existed
+pub async fn delete_admin_token(db: &SqlitePool, name: &str) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(r#"DELETE FROM admin_tokens WHERE name = ?"#, name)
+        .execute(db)
+        .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A comment as shown to the admins, regardless of its moderation state
+#[derive(Debug, Clone)]
+pub struct ModerationRow {
+    pub id: i64,
+    pub uri: String,
+    pub url: Option<String>,
+    pub blog_url: String,
+    pub reply_to_id: Option<i64>,
+    pub content: String,
+    pub summary: Option<String>,
+    pub published: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+    pub moderation_state: ModerationState,
+    pub author_uri: String,
+    pub author_username: String,
+    pub author_host: String,
+    pub author_display_name: Option<String>,
+}
+
+pub async fn comment_count_by_moderation_state(
+    db: &SqlitePool,
+    state: ModerationState,
+) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) FROM notes WHERE moderation_state = ? AND deleted_at IS NULL"#,
+        state
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Returns the comments that haven't been deleted in the given moderation state, oldest first
+pub async fn comments_by_moderation_state(
+    db: &SqlitePool,
+    state: ModerationState,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<ModerationRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ModerationRow,
+        r#"SELECT
+            n.id,
+            n.uri,
+            n.url,
+            b.url AS "blog_url!",
+            n.reply_to_id,
+            n.content,
+            n.summary,
+            n.published AS "published: _",
+            n.created_at AS "created_at: _",
+            n.moderation_state AS "moderation_state: ModerationState",
+            a.uri AS "author_uri!",
+            a.username AS "author_username!",
+            a.host AS "author_host!",
+            a.display_name AS "author_display_name?"
+        FROM notes n
+        JOIN blogs b ON b.id = n.blog_id
+        JOIN accounts a ON a.id = n.account_id
+        WHERE n.moderation_state = ? AND n.deleted_at IS NULL
+        ORDER BY n.id
+        LIMIT ? OFFSET ?"#,
+        state,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn moderation_comment_by_id(
+    db: &SqlitePool,
+    id: i64,
+) -> Result<Option<ModerationRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ModerationRow,
+        r#"SELECT
+            n.id,
+            n.uri,
+            n.url,
+            b.url AS "blog_url!",
+            n.reply_to_id,
+            n.content,
+            n.summary,
+            n.published AS "published: _",
+            n.created_at AS "created_at: _",
+            n.moderation_state AS "moderation_state: ModerationState",
+            a.uri AS "author_uri!",
+            a.username AS "author_username!",
+            a.host AS "author_host!",
+            a.display_name AS "author_display_name?"
+        FROM notes n
+        JOIN blogs b ON b.id = n.blog_id
+        JOIN accounts a ON a.id = n.account_id
+        WHERE n.id = ?"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Sets the moderation state of the comment, returns whether the comment exists
+pub async fn set_moderation_state(
+    db: &SqlitePool,
+    id: i64,
+    state: ModerationState,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE notes SET moderation_state = ? WHERE id = ?"#,
+        state,
+        id
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+/// A blog post together with the number of its comments
+#[derive(Debug, Clone)]
+pub struct BlogRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub url: String,
+    pub moderation_policy: ModerationPolicy,
+    pub approved: i64,
+    pub pending: i64,
+}
+
+pub async fn blog_count(db: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT COUNT(*) FROM blogs"#)
+        .fetch_one(db)
+        .await
+}
+
+/// Returns the blog posts, newest first
+pub async fn blogs(db: &SqlitePool, offset: i64, limit: i64) -> Result<Vec<BlogRow>, sqlx::Error> {
+    sqlx::query_as!(
+        BlogRow,
+        r#"SELECT
+            b.id,
+            b.created_at AS "created_at: _",
+            b.url,
+            b.moderation_policy AS "moderation_policy: ModerationPolicy",
+            COUNT(CASE WHEN n.moderation_state = 'approved' THEN 1 END) AS "approved!: i64",
+            COUNT(CASE WHEN n.moderation_state = 'pending' THEN 1 END) AS "pending!: i64"
+        FROM blogs b
+        LEFT JOIN notes n ON n.blog_id = b.id AND n.deleted_at IS NULL
+        GROUP BY b.id
+        ORDER BY b.id DESC
+        LIMIT ? OFFSET ?"#,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn blog_row_by_id(db: &SqlitePool, id: i64) -> Result<Option<BlogRow>, sqlx::Error> {
+    sqlx::query_as!(
+        BlogRow,
+        r#"SELECT
+            b.id,
+            b.created_at AS "created_at: _",
+            b.url,
+            b.moderation_policy AS "moderation_policy: ModerationPolicy",
+            COUNT(CASE WHEN n.moderation_state = 'approved' THEN 1 END) AS "approved!: i64",
+            COUNT(CASE WHEN n.moderation_state = 'pending' THEN 1 END) AS "pending!: i64"
+        FROM blogs b
+        LEFT JOIN notes n ON n.blog_id = b.id AND n.deleted_at IS NULL
+        WHERE b.id = ?
+        GROUP BY b.id"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Sets the moderation policy of the blog, returns whether the blog exists
+pub async fn set_moderation_policy(
+    db: &SqlitePool,
+    id: i64,
+    policy: ModerationPolicy,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE blogs SET moderation_policy = ? WHERE id = ?"#,
+        policy,
+        id
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
+#[derive(Debug, Clone)]
+pub struct DomainBlockRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub domain: String,
+    pub reason: Option<String>,
+}
+
+pub async fn domain_blocks(db: &SqlitePool) -> Result<Vec<DomainBlockRow>, sqlx::Error> {
+    sqlx::query_as!(
+        DomainBlockRow,
+        r#"SELECT id, created_at AS "created_at: _", domain, reason
+        FROM domain_blocks
+        ORDER BY domain"#
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Blocks the (normalized) domain, blocking an already blocked domain updates the reason
+pub async fn new_domain_block(
+    db: &SqlitePool,
+    domain: &str,
+    reason: Option<&str>,
+) -> Result<DomainBlockRow, sqlx::Error> {
+    sqlx::query_as!(
+        DomainBlockRow,
+        r#"INSERT INTO domain_blocks (domain, reason)
+        VALUES (?, ?)
+        ON CONFLICT (domain) DO UPDATE SET reason = excluded.reason
+        RETURNING id, created_at AS "created_at: _", domain, reason"#,
+        domain,
+        reason
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Removes the domain block and returns it, `None` if there was no such block
+pub async fn delete_domain_block(
+    db: &SqlitePool,
+    id: i64,
+) -> Result<Option<DomainBlockRow>, sqlx::Error> {
+    sqlx::query_as!(
+        DomainBlockRow,
+        r#"DELETE FROM domain_blocks WHERE id = ?
+        RETURNING id, created_at AS "created_at: _", domain, reason"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+#[derive(Debug, Clone)]
+pub struct ActorBlockRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub uri: String,
+    pub reason: Option<String>,
+}
+
+pub async fn actor_blocks(db: &SqlitePool) -> Result<Vec<ActorBlockRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ActorBlockRow,
+        r#"SELECT id, created_at AS "created_at: _", uri, reason
+        FROM actor_blocks
+        ORDER BY uri"#
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Blocks the actor, blocking an already blocked actor updates the reason
+pub async fn new_actor_block(
+    db: &SqlitePool,
+    uri: &str,
+    reason: Option<&str>,
+) -> Result<ActorBlockRow, sqlx::Error> {
+    sqlx::query_as!(
+        ActorBlockRow,
+        r#"INSERT INTO actor_blocks (uri, reason)
+        VALUES (?, ?)
+        ON CONFLICT (uri) DO UPDATE SET reason = excluded.reason
+        RETURNING id, created_at AS "created_at: _", uri, reason"#,
+        uri,
+        reason
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Removes the actor block and returns it, `None` if there was no such block
+pub async fn delete_actor_block(
+    db: &SqlitePool,
+    id: i64,
+) -> Result<Option<ActorBlockRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ActorBlockRow,
+        r#"DELETE FROM actor_blocks WHERE id = ?
+        RETURNING id, created_at AS "created_at: _", uri, reason"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Returns the host of the fediscus account itself
+pub async fn local_host(db: &SqlitePool) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT host FROM accounts WHERE local = TRUE LIMIT 1"#)
+        .fetch_optional(db)
+        .await
+}
+
+/// A remote account together with the number of its comments
+#[derive(Debug, Clone)]
+pub struct AccountRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+    pub uri: String,
+    pub username: String,
+    pub host: String,
+    pub display_name: Option<String>,
+    pub url: Option<String>,
+    pub bot: bool,
+    pub comments: i64,
+}
+
+/// Returns the number of remote accounts, optionally only those from the given host
+pub async fn account_count(db: &SqlitePool, host: Option<&str>) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) FROM accounts WHERE local = FALSE AND (? IS NULL OR host = ?)"#,
+        host,
+        host
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Returns the remote accounts, optionally only those from the given host, ordered by their
+/// address
+pub async fn accounts(
+    db: &SqlitePool,
+    host: Option<&str>,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<AccountRow>, sqlx::Error> {
+    sqlx::query_as!(
+        AccountRow,
+        r#"SELECT
+            a.id,
+            a.created_at AS "created_at: _",
+            a.updated_at AS "updated_at: _",
+            a.uri,
+            a.username,
+            a.host,
+            a.display_name,
+            a.url,
+            a.bot,
+            (SELECT COUNT(*) FROM notes n WHERE n.account_id = a.id) AS "comments!: i64"
+        FROM accounts a
+        WHERE a.local = FALSE AND (? IS NULL OR a.host = ?)
+        ORDER BY a.host, a.username
+        LIMIT ? OFFSET ?"#,
+        host,
+        host,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// Whether there is a remote account with the given ActivityPub ID
+pub async fn remote_account_exists(db: &SqlitePool, uri: &str) -> Result<bool, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT EXISTS(SELECT 1 FROM accounts WHERE uri = ? AND local = FALSE) AS "exists!: bool""#,
+        uri
+    )
+    .fetch_one(db)
+    .await
+}
+
+#[derive(Debug, Clone)]
+pub struct JobRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub kind: JobKind,
+    pub target: String,
+    pub started_at: Option<NaiveDateTime>,
+    pub finished_at: Option<NaiveDateTime>,
+    pub error: Option<String>,
+}
+
+/// Requests a job to be run by fediscus-activitypub
+pub async fn new_job(db: &SqlitePool, kind: JobKind, target: &str) -> Result<JobRow, sqlx::Error> {
+    sqlx::query_as!(
+        JobRow,
+        r#"INSERT INTO jobs (kind, target)
+        VALUES (?, ?)
+        RETURNING
+            id,
+            created_at AS "created_at: _",
+            kind AS "kind: JobKind",
+            target,
+            started_at AS "started_at: _",
+            finished_at AS "finished_at: _",
+            error"#,
+        kind,
+        target
+    )
+    .fetch_one(db)
+    .await
+}
+
+pub async fn job_by_id(db: &SqlitePool, id: i64) -> Result<Option<JobRow>, sqlx::Error> {
+    sqlx::query_as!(
+        JobRow,
+        r#"SELECT
+            id,
+            created_at AS "created_at: _",
+            kind AS "kind: JobKind",
+            target,
+            started_at AS "started_at: _",
+            finished_at AS "finished_at: _",
+            error
+        FROM jobs
+        WHERE id = ?"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+pub async fn job_count(db: &SqlitePool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(r#"SELECT COUNT(*) FROM jobs"#)
+        .fetch_one(db)
+        .await
+}
+
+/// Returns the jobs, newest first
+pub async fn jobs(db: &SqlitePool, offset: i64, limit: i64) -> Result<Vec<JobRow>, sqlx::Error> {
+    sqlx::query_as!(
+        JobRow,
+        r#"SELECT
+            id,
+            created_at AS "created_at: _",
+            kind AS "kind: JobKind",
+            target,
+            started_at AS "started_at: _",
+            finished_at AS "finished_at: _",
+            error
+        FROM jobs
+        ORDER BY id DESC
+        LIMIT ? OFFSET ?"#,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+/// A report received from another instance together with its reporter
+#[derive(Debug, Clone)]
+pub struct ReportRow {
+    pub id: i64,
+    pub created_at: NaiveDateTime,
+    pub uri: String,
+    pub content: Option<String>,
+    /// JSON array of the URIs of the reported accounts and notes
+    pub objects: String,
+    pub resolved_at: Option<NaiveDateTime>,
+    pub reporter_uri: String,
+    pub reporter_username: String,
+    pub reporter_host: String,
+    pub reporter_display_name: Option<String>,
+}
+
+pub async fn report_count(db: &SqlitePool, resolved: bool) -> Result<i64, sqlx::Error> {
+    sqlx::query_scalar!(
+        r#"SELECT COUNT(*) FROM reports WHERE (resolved_at IS NOT NULL) = ?"#,
+        resolved
+    )
+    .fetch_one(db)
+    .await
+}
+
+/// Returns the resolved or the unresolved reports, oldest first
+pub async fn reports(
+    db: &SqlitePool,
+    resolved: bool,
+    offset: i64,
+    limit: i64,
+) -> Result<Vec<ReportRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ReportRow,
+        r#"SELECT
+            r.id,
+            r.created_at AS "created_at: _",
+            r.uri,
+            r.content,
+            r.objects,
+            r.resolved_at AS "resolved_at: _",
+            a.uri AS "reporter_uri!",
+            a.username AS "reporter_username!",
+            a.host AS "reporter_host!",
+            a.display_name AS "reporter_display_name?"
+        FROM reports r
+        JOIN accounts a ON a.id = r.account_id
+        WHERE (r.resolved_at IS NOT NULL) = ?
+        ORDER BY r.id
+        LIMIT ? OFFSET ?"#,
+        resolved,
+        limit,
+        offset
+    )
+    .fetch_all(db)
+    .await
+}
+
+pub async fn report_by_id(db: &SqlitePool, id: i64) -> Result<Option<ReportRow>, sqlx::Error> {
+    sqlx::query_as!(
+        ReportRow,
+        r#"SELECT
+            r.id,
+            r.created_at AS "created_at: _",
+            r.uri,
+            r.content,
+            r.objects,
+            r.resolved_at AS "resolved_at: _",
+            a.uri AS "reporter_uri!",
+            a.username AS "reporter_username!",
+            a.host AS "reporter_host!",
+            a.display_name AS "reporter_display_name?"
+        FROM reports r
+        JOIN accounts a ON a.id = r.account_id
+        WHERE r.id = ?"#,
+        id
+    )
+    .fetch_optional(db)
+    .await
+}
+
+/// Marks the report as resolved, returns whether the report exists
+pub async fn resolve_report(db: &SqlitePool, id: i64) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query!(
+        r#"UPDATE reports SET resolved_at = COALESCE(resolved_at, DATETIME('now')) WHERE id = ?"#,
+        id
+    )
+    .execute(db)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
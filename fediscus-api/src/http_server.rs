@@ -1,47 +1,354 @@
-use aide::axum::{routing::get, ApiRouter};
-use aide::openapi::{Info, OpenApi};
+use std::sync::Arc;
+
+use aide::axum::{
+    routing::{delete, get, post, put},
+    ApiRouter,
+};
+use aide::openapi::{Info, OpenApi, SecurityScheme};
 use aide::swagger::Swagger;
 
 use anyhow::Error;
-use axum::Extension;
+use axum::{Extension, Router};
+use fediscus_activitypub::SqliteStorage;
 use fediscus_common::http_server::HttpServerConfig;
+use sqlx::SqlitePool;
 use tokio::net::TcpListener;
 
+mod admin;
+mod auth;
+mod error;
 mod handlers;
+mod thread;
+
+pub use auth::{generate_token, hash_token};
+pub use error::ApiError;
+
+pub struct AppState {
+    pub db: SqlitePool,
+    /// Storage of fediscus-activitypub on top of the same database
+    pub storage: SqliteStorage,
+}
+
+impl AppState {
+    pub fn new(db: SqlitePool) -> Self {
+        let storage = SqliteStorage::with_pool(db.clone());
+        Self { db, storage }
+    }
+}
 
 pub struct HttpServer {
     config: HttpServerConfig,
+    db: SqlitePool,
 }
 
 impl HttpServer {
-    pub fn new(config: HttpServerConfig) -> Self {
-        HttpServer { config }
+    pub fn new(config: HttpServerConfig, db: SqlitePool) -> Self {
+        HttpServer { config, db }
     }
 
     pub async fn run(self) -> Result<(), Error> {
         let listener = TcpListener::bind(self.config.listen).await?;
+        let state = Arc::new(AppState::new(self.db));
+
+        Ok(axum::serve(listener, router(state).into_make_service()).await?)
+    }
+}
+
+/// Builds the router serving the API and its OpenAPI spec
+fn router(state: Arc<AppState>) -> Router {
+    let router = ApiRouter::new()
+        .route("/api", Swagger::new("/api.json").axum_route())
+        .api_route("/api/v1/comments", get(handlers::get_comments))
+        .api_route("/api/v1/comments/thread", get(handlers::get_thread))
+        .api_route("/api/v1/comment_counts", post(handlers::get_comment_counts))
+        .api_route("/api/v1/admin/comments", get(admin::get_moderation_queue))
+        .api_route(
+            "/api/v1/admin/comments/{id}/moderation",
+            put(admin::put_comment_moderation),
+        )
+        .api_route("/api/v1/admin/blogs", get(admin::get_blogs))
+        .api_route(
+            "/api/v1/admin/blogs/{id}/moderation_policy",
+            put(admin::put_blog_moderation_policy),
+        )
+        .api_route(
+            "/api/v1/admin/blocks/domains",
+            get(admin::get_domain_blocks).post(admin::post_domain_block),
+        )
+        .api_route(
+            "/api/v1/admin/blocks/domains/{id}",
+            delete(admin::delete_domain_block),
+        )
+        .api_route(
+            "/api/v1/admin/blocks/actors",
+            get(admin::get_actor_blocks).post(admin::post_actor_block),
+        )
+        .api_route(
+            "/api/v1/admin/blocks/actors/{id}",
+            delete(admin::delete_actor_block),
+        )
+        .api_route("/api/v1/admin/accounts", get(admin::get_accounts))
+        .api_route(
+            "/api/v1/admin/jobs",
+            get(admin::get_jobs).post(admin::post_job),
+        )
+        .api_route("/api/v1/admin/jobs/{id}", get(admin::get_job))
+        .api_route("/api/v1/admin/reports", get(admin::get_reports))
+        .api_route(
+            "/api/v1/admin/reports/{id}/resolve",
+            post(admin::post_report_resolve),
+        )
+        .route("/api.json", get(handlers::serve_api));
+
+    let mut api = OpenApi {
+        info: Info {
+            description: Some("Fediscus API".to_string()),
+            ..Info::default()
+        },
+        ..OpenApi::default()
+    };
+
+    router
+        .finish_api_with(&mut api, |api| {
+            api.security_scheme(
+                auth::ADMIN_SECURITY_SCHEME,
+                SecurityScheme::Http {
+                    scheme: "bearer".to_string(),
+                    bearer_format: None,
+                    description: Some(
+                        "Admin token created with `fediscus-api create-token <name>`".to_string(),
+                    ),
+                    extensions: Default::default(),
+                },
+            )
+        })
+        .layer(Extension(api))
+        .with_state(state)
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header, Method, Request, StatusCode};
+    use serde_json::{json, Value};
+    use sqlx::sqlite::SqlitePoolOptions;
+    use tower::ServiceExt;
+
+    use super::*;
+    use crate::db;
+
+    const TOKEN: &str = "secret";
+
+    /// Returns a router backed by a fresh in-memory database with an admin token named `test`
+    async fn setup() -> (Router, SqlitePool) {
+        // Every connection to an in-memory database gets its own, so there must be just one
+        let db = SqlitePoolOptions::new()
+            .max_connections(1)
+            .connect("sqlite::memory:")
+            .await
+            .unwrap();
+        sqlx::migrate!("../fediscus-database/migrations")
+            .run(&db)
+            .await
+            .unwrap();
+        db::new_admin_token(&db, "test", &hash_token(TOKEN))
+            .await
+            .unwrap();
+
+        let state = Arc::new(AppState::new(db.clone()));
+        (router(state), db)
+    }
+
+    async fn request(
+        router: &Router,
+        method: Method,
+        uri: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let mut request = Request::builder().method(method).uri(uri);
+        if let Some(token) = token {
+            request = request.header(header::AUTHORIZATION, format!("Bearer {}", token));
+        }
+        let request = match body {
+            Some(body) => request
+                .header(header::CONTENT_TYPE, "application/json")
+                .body(Body::from(body.to_string())),
+            None => request.body(Body::empty()),
+        }
+        .unwrap();
+
+        let response = router.clone().oneshot(request).await.unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            serde_json::from_slice(&bytes).unwrap_or(Value::Null),
+        )
+    }
+
+    /// Adds a remote account with a single comment on a blog post, returns the ID of the comment
+    async fn insert_comment(db: &SqlitePool) -> i64 {
+        sqlx::query(
+            r#"INSERT INTO accounts (uri, username, host, inbox, outbox, public_key, local)
+            VALUES ('https://remote.example/user', 'user', 'remote.example',
+                'https://remote.example/user/inbox', 'https://remote.example/user/outbox', '',
+                FALSE)"#,
+        )
+        .execute(db)
+        .await
+        .unwrap();
+        sqlx::query(r#"INSERT INTO blogs (url) VALUES ('https://example.com/blog-post')"#)
+            .execute(db)
+            .await
+            .unwrap();
+        sqlx::query(
+            r#"INSERT INTO notes (account_id, uri, blog_id, content, moderation_state)
+            VALUES (1, 'https://remote.example/note/1', 1, 'Hello', 'pending')"#,
+        )
+        .execute(db)
+        .await
+        .unwrap()
+        .last_insert_rowid()
+    }
+
+    #[tokio::test]
+    async fn test_admin_token_required() {
+        let (router, db) = setup().await;
+        let uri = "/api/v1/admin/comments";
+
+        let (status, _) = request(&router, Method::GET, uri, None, None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = request(&router, Method::GET, uri, Some("wrong"), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        let (status, _) = request(&router, Method::GET, uri, Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::OK);
+
+        assert!(db::delete_admin_token(&db, "test").await.unwrap());
+        let (status, _) = request(&router, Method::GET, uri, Some(TOKEN), None).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_comment_moderation() {
+        let (router, db) = setup().await;
+        let id = insert_comment(&db).await;
+
+        let (status, queue) = request(
+            &router,
+            Method::GET,
+            "/api/v1/admin/comments",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(queue["total"], 1);
+
+        let uri = format!("/api/v1/admin/comments/{}/moderation", id);
+        let (status, comment) = request(
+            &router,
+            Method::PUT,
+            &uri,
+            Some(TOKEN),
+            Some(json!({ "state": "approved" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(comment["moderation_state"], "approved");
+
+        let (_, queue) = request(
+            &router,
+            Method::GET,
+            "/api/v1/admin/comments",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(queue["total"], 0);
 
-        let router = ApiRouter::new()
-            .route("/api", Swagger::new("/api.json").axum_route())
-            .api_route("/api/v1/comments", get(handlers::get_comments))
-            //.api_route("/api/v1/comment_counts", post(handlers::comment_count))
-            .route("/api.json", get(handlers::serve_api));
-
-        let mut api = OpenApi {
-            info: Info {
-                description: Some("Fediscus API".to_string()),
-                ..Info::default()
-            },
-            ..OpenApi::default()
-        };
-
-        Ok(axum::serve(
-            listener,
-            router
-                .finish_api(&mut api)
-                .layer(Extension(api))
-                .into_make_service(),
-        )
-        .await?)
+        let (status, _) = request(
+            &router,
+            Method::PUT,
+            "/api/v1/admin/comments/1000/moderation",
+            Some(TOKEN),
+            Some(json!({ "state": "approved" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_jobs() {
+        let (router, db) = setup().await;
+        insert_comment(&db).await;
+
+        let (status, job) = request(
+            &router,
+            Method::POST,
+            "/api/v1/admin/jobs",
+            Some(TOKEN),
+            Some(json!({ "kind": "backfill", "target": "https://example.com/blog-post" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(job["kind"], "backfill");
+        assert!(job["started_at"].is_null());
+
+        let (status, fetched) = request(
+            &router,
+            Method::GET,
+            &format!("/api/v1/admin/jobs/{}", job["id"]),
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(fetched, job);
+
+        // Only known blog posts and accounts can be the target of a job
+        let (status, _) = request(
+            &router,
+            Method::POST,
+            "/api/v1/admin/jobs",
+            Some(TOKEN),
+            Some(json!({ "kind": "refresh_account", "target": "https://unknown.example/user" })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_page_size_limit() {
+        let (router, _) = setup().await;
+
+        let (status, _) = request(
+            &router,
+            Method::GET,
+            "/api/v1/admin/comments?limit=200",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let (status, _) = request(
+            &router,
+            Method::GET,
+            "/api/v1/admin/comments?limit=201",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        // Offsets that don't fit the database are rejected rather than wrapped around
+        let (status, _) = request(
+            &router,
+            Method::GET,
+            "/api/v1/admin/comments?offset=18446744073709551615",
+            Some(TOKEN),
+            None,
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
     }
 }
@@ -0,0 +1,632 @@
+//! Endpoints of the admin API, all of them require an admin token.
+
+use std::sync::Arc;
+
+use axum::extract::{Path, Query, State};
+use axum::Json;
+use chrono::{DateTime, Utc};
+use fediscus_common::domain::{is_subdomain, normalize_domain};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use crate::db::{self, JobKind, ModerationPolicy, ModerationState};
+
+use super::auth::Admin;
+use super::{ApiError, AppState};
+
+/// Number of items returned when the request doesn't specify a limit
+const DEFAULT_PAGE_SIZE: usize = 50;
+
+/// Maximum number of items that can be requested at once
+const MAX_PAGE_SIZE: usize = 200;
+
+/// Validates the requested page and turns it into the offset and limit for the database
+fn page_bounds(offset: usize, limit: Option<usize>) -> Result<(i64, i64), ApiError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if limit > MAX_PAGE_SIZE {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} items can be requested at once",
+            MAX_PAGE_SIZE
+        )));
+    }
+    let offset = i64::try_from(offset)
+        .map_err(|_| ApiError::BadRequest(format!("Offset {} is out of range", offset)))?;
+    Ok((offset, limit as i64))
+}
+
+/// A page of a longer list
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Page<T> {
+    /// Total number of items in the list
+    pub total: i64,
+    pub items: Vec<T>,
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct IdPath {
+    pub id: i64,
+}
+
+/// A fediverse account as shown in the admin API
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AccountSummary {
+    /// ActivityPub ID of the account
+    pub uri: String,
+    /// The username of the account (without the host)
+    pub username: String,
+    /// The instance the account lives on
+    pub host: String,
+    pub display_name: Option<String>,
+}
+
+/// A comment together with its moderation state
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ModeratedComment {
+    /// Fediscus ID of the comment
+    pub id: i64,
+    /// ActivityPub ID of the note
+    pub uri: String,
+    /// Link to the note on the author's instance
+    pub url: Option<String>,
+    /// URL of the blog post the comment belongs to
+    pub blog_url: String,
+    /// ID of the comment this comment is a reply to
+    pub in_reply_to_id: Option<i64>,
+    pub author: AccountSummary,
+    /// HTML content of the comment
+    pub content: String,
+    /// Content warning
+    pub summary: Option<String>,
+    /// When the comment was published, as reported by the author's instance
+    pub published: Option<DateTime<Utc>>,
+    /// When fediscus first saw the comment
+    pub created_at: DateTime<Utc>,
+    pub moderation_state: ModerationState,
+}
+
+impl From<db::ModerationRow> for ModeratedComment {
+    fn from(row: db::ModerationRow) -> Self {
+        Self {
+            id: row.id,
+            uri: row.uri,
+            url: row.url,
+            blog_url: row.blog_url,
+            in_reply_to_id: row.reply_to_id,
+            author: AccountSummary {
+                uri: row.author_uri,
+                username: row.author_username,
+                host: row.author_host,
+                display_name: row.author_display_name,
+            },
+            content: row.content,
+            summary: row.summary,
+            published: row.published.map(|published| published.and_utc()),
+            created_at: row.created_at.and_utc(),
+            moderation_state: row.moderation_state,
+        }
+    }
+}
+
+const fn default_moderation_state() -> ModerationState {
+    ModerationState::Pending
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetModerationQueue {
+    /// Moderation state of the comments to list, the comments held for moderation by default
+    #[serde(default = "default_moderation_state")]
+    pub state: ModerationState,
+    /// Number of comments to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of comments to return, 50 if not set
+    pub limit: Option<usize>,
+}
+
+/// Lists the comments in the given moderation state, oldest first
+pub async fn get_moderation_queue(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetModerationQueue>,
+) -> Result<Json<Page<ModeratedComment>>, ApiError> {
+    let (offset, limit) = page_bounds(query.offset, query.limit)?;
+    let total = db::comment_count_by_moderation_state(&state.db, query.state).await?;
+    let items = db::comments_by_moderation_state(&state.db, query.state, offset, limit)
+        .await?
+        .into_iter()
+        .map(ModeratedComment::from)
+        .collect();
+
+    Ok(Json(Page { total, items }))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetModerationState {
+    /// The new moderation state, only approved comments are shown publicly
+    pub state: ModerationState,
+}
+
+/// Approves, rejects or hides a comment
+pub async fn put_comment_moderation(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+    Json(request): Json<SetModerationState>,
+) -> Result<Json<ModeratedComment>, ApiError> {
+    if !db::set_moderation_state(&state.db, path.id, request.state).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    let comment = db::moderation_comment_by_id(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(comment.into()))
+}
+
+/// A blog post known to fediscus
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AdminBlog {
+    pub id: i64,
+    /// URL of the blog post
+    pub url: String,
+    /// When fediscus first learned about the blog post
+    pub created_at: DateTime<Utc>,
+    pub moderation_policy: ModerationPolicy,
+    /// Number of publicly shown comments, including the root notes
+    pub approved: i64,
+    /// Number of comments held for moderation
+    pub pending: i64,
+}
+
+impl From<db::BlogRow> for AdminBlog {
+    fn from(row: db::BlogRow) -> Self {
+        Self {
+            id: row.id,
+            url: row.url,
+            created_at: row.created_at.and_utc(),
+            moderation_policy: row.moderation_policy,
+            approved: row.approved,
+            pending: row.pending,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetBlogs {
+    /// Number of blog posts to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of blog posts to return, 50 if not set
+    pub limit: Option<usize>,
+}
+
+/// Lists the blog posts, newest first
+pub async fn get_blogs(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetBlogs>,
+) -> Result<Json<Page<AdminBlog>>, ApiError> {
+    let (offset, limit) = page_bounds(query.offset, query.limit)?;
+    let total = db::blog_count(&state.db).await?;
+    let items = db::blogs(&state.db, offset, limit)
+        .await?
+        .into_iter()
+        .map(AdminBlog::from)
+        .collect();
+
+    Ok(Json(Page { total, items }))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct SetModerationPolicy {
+    /// How new replies to the blog post are moderated, existing comments are not affected
+    pub policy: ModerationPolicy,
+}
+
+/// Sets how new replies to the blog post are moderated
+pub async fn put_blog_moderation_policy(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+    Json(request): Json<SetModerationPolicy>,
+) -> Result<Json<AdminBlog>, ApiError> {
+    if !db::set_moderation_policy(&state.db, path.id, request.policy).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    let blog = db::blog_row_by_id(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(blog.into()))
+}
+
+/// A blocked instance, blocks apply to its subdomains as well
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct DomainBlock {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    pub domain: String,
+    pub reason: Option<String>,
+}
+
+impl From<db::DomainBlockRow> for DomainBlock {
+    fn from(row: db::DomainBlockRow) -> Self {
+        Self {
+            id: row.id,
+            created_at: row.created_at.and_utc(),
+            domain: row.domain,
+            reason: row.reason,
+        }
+    }
+}
+
+/// Lists the blocked domains
+pub async fn get_domain_blocks(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<DomainBlock>>, ApiError> {
+    let blocks = db::domain_blocks(&state.db)
+        .await?
+        .into_iter()
+        .map(DomainBlock::from)
+        .collect();
+    Ok(Json(blocks))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NewDomainBlock {
+    /// The domain to block, e.g. `spam.example`
+    pub domain: String,
+    pub reason: Option<String>,
+}
+
+/// Blocks a domain and all its subdomains, blocking an already blocked domain updates the
+/// reason. Comments already received from the domain are kept.
+pub async fn post_domain_block(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<NewDomainBlock>,
+) -> Result<Json<DomainBlock>, ApiError> {
+    let domain = normalize_domain(&request.domain);
+    if domain.is_empty() {
+        return Err(ApiError::BadRequest("Domain must not be empty".to_string()));
+    }
+    let local_host = db::local_host(&state.db).await?.unwrap_or_default();
+    if is_subdomain(&local_host, &domain) {
+        return Err(ApiError::BadRequest(format!(
+            "Refusing to block domain '{}'",
+            domain
+        )));
+    }
+
+    let block = db::new_domain_block(&state.db, &domain, request.reason.as_deref()).await?;
+    Ok(Json(block.into()))
+}
+
+/// Unblocks a domain
+pub async fn delete_domain_block(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+) -> Result<Json<DomainBlock>, ApiError> {
+    let block = db::delete_domain_block(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(block.into()))
+}
+
+/// A blocked fediverse actor
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ActorBlock {
+    pub id: i64,
+    pub created_at: DateTime<Utc>,
+    /// ActivityPub ID of the actor
+    pub uri: String,
+    pub reason: Option<String>,
+}
+
+impl From<db::ActorBlockRow> for ActorBlock {
+    fn from(row: db::ActorBlockRow) -> Self {
+        Self {
+            id: row.id,
+            created_at: row.created_at.and_utc(),
+            uri: row.uri,
+            reason: row.reason,
+        }
+    }
+}
+
+/// Lists the blocked actors
+pub async fn get_actor_blocks(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+) -> Result<Json<Vec<ActorBlock>>, ApiError> {
+    let blocks = db::actor_blocks(&state.db)
+        .await?
+        .into_iter()
+        .map(ActorBlock::from)
+        .collect();
+    Ok(Json(blocks))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NewActorBlock {
+    /// ActivityPub ID of the actor to block, the actor doesn't need to be known to fediscus
+    pub uri: String,
+    pub reason: Option<String>,
+}
+
+/// Blocks an actor, blocking an already blocked actor updates the reason
+pub async fn post_actor_block(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<NewActorBlock>,
+) -> Result<Json<ActorBlock>, ApiError> {
+    let uri = Url::parse(&request.uri)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid actor URI: {}", e)))?;
+    let block = db::new_actor_block(&state.db, uri.as_str(), request.reason.as_deref()).await?;
+    Ok(Json(block.into()))
+}
+
+/// Unblocks an actor
+pub async fn delete_actor_block(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+) -> Result<Json<ActorBlock>, ApiError> {
+    let block = db::delete_actor_block(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(block.into()))
+}
+
+/// A remote account known to fediscus
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct AdminAccount {
+    pub id: i64,
+    /// ActivityPub ID of the account
+    pub uri: String,
+    /// The username of the account (without the host)
+    pub username: String,
+    /// The instance the account lives on
+    pub host: String,
+    pub display_name: Option<String>,
+    /// Link to the account's profile page on its instance
+    pub url: Option<String>,
+    /// Whether the account is an automated account
+    pub bot: bool,
+    /// When fediscus first saw the account
+    pub created_at: DateTime<Utc>,
+    /// When fediscus last fetched the account
+    pub updated_at: DateTime<Utc>,
+    /// Number of notes of the account, regardless of their moderation state
+    pub comments: i64,
+}
+
+impl From<db::AccountRow> for AdminAccount {
+    fn from(row: db::AccountRow) -> Self {
+        Self {
+            id: row.id,
+            uri: row.uri,
+            username: row.username,
+            host: row.host,
+            display_name: row.display_name,
+            url: row.url,
+            bot: row.bot,
+            created_at: row.created_at.and_utc(),
+            updated_at: row.updated_at.and_utc(),
+            comments: row.comments,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetAccounts {
+    /// Only list the accounts from this instance
+    pub host: Option<String>,
+    /// Number of accounts to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of accounts to return, 50 if not set
+    pub limit: Option<usize>,
+}
+
+/// Lists the remote accounts, ordered by their instance and username
+pub async fn get_accounts(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetAccounts>,
+) -> Result<Json<Page<AdminAccount>>, ApiError> {
+    let (offset, limit) = page_bounds(query.offset, query.limit)?;
+    let host = query.host.as_deref();
+    let total = db::account_count(&state.db, host).await?;
+    let items = db::accounts(&state.db, host, offset, limit)
+        .await?
+        .into_iter()
+        .map(AdminAccount::from)
+        .collect();
+
+    Ok(Json(Page { total, items }))
+}
+
+/// A job run in the background by the fediscus ActivityPub server
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Job {
+    pub id: i64,
+    pub kind: JobKind,
+    /// URL of the blog post or ActivityPub ID of the account, depending on the kind of the job
+    pub target: String,
+    /// When the job was requested
+    pub created_at: DateTime<Utc>,
+    /// When the job was picked up, not set while it's waiting to be run
+    pub started_at: Option<DateTime<Utc>>,
+    /// When the job was done, not set while it's waiting or running
+    pub finished_at: Option<DateTime<Utc>>,
+    /// Why the job has failed, not set if it succeeded
+    pub error: Option<String>,
+}
+
+impl From<db::JobRow> for Job {
+    fn from(row: db::JobRow) -> Self {
+        Self {
+            id: row.id,
+            kind: row.kind,
+            target: row.target,
+            created_at: row.created_at.and_utc(),
+            started_at: row.started_at.map(|started_at| started_at.and_utc()),
+            finished_at: row.finished_at.map(|finished_at| finished_at.and_utc()),
+            error: row.error,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetJobs {
+    /// Number of jobs to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of jobs to return, 50 if not set
+    pub limit: Option<usize>,
+}
+
+/// Lists the jobs, newest first
+pub async fn get_jobs(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetJobs>,
+) -> Result<Json<Page<Job>>, ApiError> {
+    let (offset, limit) = page_bounds(query.offset, query.limit)?;
+    let total = db::job_count(&state.db).await?;
+    let items = db::jobs(&state.db, offset, limit)
+        .await?
+        .into_iter()
+        .map(Job::from)
+        .collect();
+
+    Ok(Json(Page { total, items }))
+}
+
+/// Returns a single job, poll it to learn when the job is done
+pub async fn get_job(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+) -> Result<Json<Job>, ApiError> {
+    let job = db::job_by_id(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(job.into()))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct NewJob {
+    pub kind: JobKind,
+    /// URL of a known blog post for backfills, ActivityPub ID of a known remote account for
+    /// account refreshes
+    pub target: String,
+}
+
+/// Requests a backfill of a blog post or a refresh of a remote account. The job is run in the
+/// background by the fediscus ActivityPub server.
+pub async fn post_job(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<NewJob>,
+) -> Result<Json<Job>, ApiError> {
+    let known = match request.kind {
+        JobKind::Backfill => db::blog_by_url(&state.db, &request.target).await?.is_some(),
+        JobKind::RefreshAccount => db::remote_account_exists(&state.db, &request.target).await?,
+    };
+    if !known {
+        return Err(ApiError::BadRequest(format!(
+            "Unknown job target {}",
+            request.target
+        )));
+    }
+
+    let job = db::new_job(&state.db, request.kind, &request.target).await?;
+    Ok(Json(job.into()))
+}
+
+/// A report of our content sent by the moderators of another instance
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Report {
+    pub id: i64,
+    /// ActivityPub ID of the Flag activity
+    pub uri: String,
+    pub reporter: AccountSummary,
+    /// Comment of the reporter
+    pub content: Option<String>,
+    /// ActivityPub IDs of the reported accounts and notes
+    pub objects: Vec<String>,
+    /// When fediscus received the report
+    pub created_at: DateTime<Utc>,
+    /// When the report was marked as resolved, not set while it awaits review
+    pub resolved_at: Option<DateTime<Utc>>,
+}
+
+impl From<db::ReportRow> for Report {
+    fn from(row: db::ReportRow) -> Self {
+        Self {
+            id: row.id,
+            uri: row.uri,
+            reporter: AccountSummary {
+                uri: row.reporter_uri,
+                username: row.reporter_username,
+                host: row.reporter_host,
+                display_name: row.reporter_display_name,
+            },
+            content: row.content,
+            // The column is only ever written by fediscus-activitypub as a JSON array
+            objects: serde_json::from_str(&row.objects).unwrap_or_default(),
+            created_at: row.created_at.and_utc(),
+            resolved_at: row.resolved_at.map(|resolved_at| resolved_at.and_utc()),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetReports {
+    /// List the resolved reports instead of the ones awaiting review
+    #[serde(default)]
+    pub resolved: bool,
+    /// Number of reports to skip
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of reports to return, 50 if not set
+    pub limit: Option<usize>,
+}
+
+/// Lists the reports received from other instances, oldest first
+pub async fn get_reports(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetReports>,
+) -> Result<Json<Page<Report>>, ApiError> {
+    let (offset, limit) = page_bounds(query.offset, query.limit)?;
+    let total = db::report_count(&state.db, query.resolved).await?;
+    let items = db::reports(&state.db, query.resolved, offset, limit)
+        .await?
+        .into_iter()
+        .map(Report::from)
+        .collect();
+
+    Ok(Json(Page { total, items }))
+}
+
+/// Marks the report as resolved once it has been reviewed
+pub async fn post_report_resolve(
+    _admin: Admin,
+    State(state): State<Arc<AppState>>,
+    Path(path): Path<IdPath>,
+) -> Result<Json<Report>, ApiError> {
+    if !db::resolve_report(&state.db, path.id).await? {
+        return Err(ApiError::NotFound);
+    }
+
+    let report = db::report_by_id(&state.db, path.id)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+    Ok(Json(report.into()))
+}
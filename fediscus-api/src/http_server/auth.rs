@@ -0,0 +1,64 @@
+use std::sync::Arc;
+
+use aide::generate::GenContext;
+use aide::openapi::Operation;
+use aide::OperationInput;
+use axum::extract::FromRequestParts;
+use axum::http::header::AUTHORIZATION;
+use axum::http::request::Parts;
+use sha2::{Digest, Sha256};
+
+use crate::db;
+
+use super::{ApiError, AppState};
+
+/// Name of the security scheme of the admin API in the OpenAPI spec
+pub const ADMIN_SECURITY_SCHEME: &str = "admin_token";
+
+/// Generates a new random admin token
+pub fn generate_token() -> String {
+    let bytes: [u8; 32] = rand::random();
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Hashes the token the way it's stored in the database
+pub fn hash_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+/// An admin authenticated by a bearer token, requests without a valid token are rejected
+pub struct Admin {
+    /// Name of the token the admin has authenticated with
+    pub token_name: String,
+}
+
+impl FromRequestParts<Arc<AppState>> for Admin {
+    type Rejection = ApiError;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &Arc<AppState>,
+    ) -> Result<Self, Self::Rejection> {
+        let token = parts
+            .headers
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .ok_or(ApiError::Unauthorized)?;
+
+        let token_name = db::use_admin_token(&state.db, &hash_token(token.trim()))
+            .await?
+            .ok_or(ApiError::Unauthorized)?;
+        Ok(Admin { token_name })
+    }
+}
+
+impl OperationInput for Admin {
+    fn operation_input(_ctx: &mut GenContext, operation: &mut Operation) {
+        operation.security.push(
+            [(ADMIN_SECURITY_SCHEME.to_string(), Vec::new())]
+                .into_iter()
+                .collect(),
+        );
+    }
+}
@@ -0,0 +1,59 @@
+use aide::OperationOutput;
+use axum::http::StatusCode;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use fediscus_activitypub::NoteError;
+use schemars::JsonSchema;
+use serde::Serialize;
+use thiserror::Error;
+use tracing::error;
+
+#[derive(Debug, Error)]
+pub enum ApiError {
+    #[error("Bad request: {0}")]
+    BadRequest(String),
+    #[error("Not found")]
+    NotFound,
+    #[error("Missing or invalid admin token")]
+    Unauthorized,
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Storage error: {0}")]
+    Storage(#[from] NoteError),
+}
+
+/// Body of an error response
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ErrorResponse {
+    /// Human-readable description of the error
+    pub error: String,
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let status = match &self {
+            ApiError::BadRequest(_) => StatusCode::BAD_REQUEST,
+            ApiError::NotFound => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized => StatusCode::UNAUTHORIZED,
+            ApiError::Database(e) => {
+                error!("Database error: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+            ApiError::Storage(e) => {
+                error!("Storage error: {}", e);
+                StatusCode::INTERNAL_SERVER_ERROR
+            }
+        };
+        (
+            status,
+            Json(ErrorResponse {
+                error: self.to_string(),
+            }),
+        )
+            .into_response()
+    }
+}
+
+impl OperationOutput for ApiError {
+    type Inner = ErrorResponse;
+}
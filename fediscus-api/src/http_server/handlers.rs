@@ -1,22 +1,312 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
 use aide::{axum::IntoApiResponse, openapi::OpenApi};
+use axum::extract::{Query, State};
 use axum::{Extension, Json};
+use chrono::{DateTime, Utc};
+use fediscus_activitypub::{NoteStorage, ThreadPage};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::db;
+
+use super::thread::{self, ThreadComment, ThreadOrder};
+use super::{ApiError, AppState};
+
 pub async fn serve_api(Extension(api): Extension<OpenApi>) -> impl IntoApiResponse {
     Json(api)
 }
 
 #[derive(Debug, Deserialize, JsonSchema)]
 pub struct GetComments {
+    /// URL of the blog post to retrieve the comments for
+    pub url: String,
+}
+
+/// Author of a comment
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Author {
+    /// ActivityPub ID of the author
+    pub uri: String,
+    /// The username of the author (without the host)
+    pub username: String,
+    /// The instance the author lives on
+    pub host: String,
+    /// The name to show instead of the username, if the author has set one
+    pub display_name: Option<String>,
+    /// URL of the author's avatar
+    pub avatar_url: Option<String>,
+    /// Link to the author's profile page on their instance
+    pub url: Option<String>,
+    /// Whether the author is an automated account
+    pub bot: bool,
+    /// The account the author has migrated to, if any
+    pub moved_to: Option<MovedTo>,
+}
+
+/// The new account of an author who has migrated
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct MovedTo {
+    /// ActivityPub ID of the new account
+    pub uri: String,
+    /// The username of the new account (without the host)
+    pub username: String,
+    /// The instance the new account lives on
+    pub host: String,
+    /// Link to the profile page of the new account
+    pub url: Option<String>,
+}
+
+/// A single comment
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Comment {
+    /// Fediscus ID of the comment
+    pub id: i64,
+    /// ActivityPub ID of the note
+    pub uri: String,
+    /// Link to the note on the author's instance
+    pub url: Option<String>,
+    /// ID of the comment this comment is a reply to, not set for the root note
+    pub in_reply_to_id: Option<i64>,
+    /// ID of the root note of the thread, not set for the root note itself
+    pub root_id: Option<i64>,
+    pub author: Author,
+    /// HTML content of the comment
+    pub content: String,
+    /// Content warning
+    pub summary: Option<String>,
+    pub language: Option<String>,
+    /// When the comment was published, as reported by the author's instance
+    pub published: Option<DateTime<Utc>>,
+    /// When fediscus first saw the comment
+    pub created_at: DateTime<Utc>,
+    /// When fediscus last updated the comment
+    pub updated_at: DateTime<Utc>,
+    /// Whether the comment has been deleted by its author. Deleted comments are kept
+    /// with empty content so that their replies remain in the thread.
+    pub deleted: bool,
+    pub likes: i64,
+    pub reposts: i64,
+    /// Number of direct replies to this comment
+    pub replies: usize,
+}
+
+impl From<db::CommentRow> for Comment {
+    fn from(row: db::CommentRow) -> Self {
+        Self {
+            id: row.id,
+            uri: row.uri,
+            url: row.url,
+            in_reply_to_id: row.reply_to_id,
+            root_id: row.root_id,
+            author: Author {
+                uri: row.author_uri,
+                username: row.author_username,
+                host: row.author_host,
+                display_name: row.author_display_name,
+                avatar_url: row.author_avatar_url,
+                url: row.author_url,
+                bot: row.author_bot,
+                moved_to: match (
+                    row.author_moved_to_uri,
+                    row.author_moved_to_username,
+                    row.author_moved_to_host,
+                ) {
+                    (Some(uri), Some(username), Some(host)) => Some(MovedTo {
+                        uri,
+                        username,
+                        host,
+                        url: row.author_moved_to_url,
+                    }),
+                    _ => None,
+                },
+            },
+            content: row.content,
+            summary: row.summary,
+            language: row.language,
+            published: row.published.map(|published| published.and_utc()),
+            created_at: row.created_at.and_utc(),
+            updated_at: row.updated_at.and_utc(),
+            deleted: row.deleted_at.is_some(),
+            likes: row.likes,
+            reposts: row.reposts,
+            replies: 0,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommentsResponse {
+    /// URL of the blog post
+    pub url: String,
+    /// Total number of notes in the thread
+    pub total: usize,
+    /// All notes in the thread, ordered from the oldest to the newest
+    pub comments: Vec<Comment>,
+}
+
+/// Loads all comments of the given blog post
+async fn load_comments(state: &AppState, url: &str) -> Result<(db::Blog, Vec<Comment>), ApiError> {
+    let blog = db::blog_by_url(&state.db, url)
+        .await?
+        .ok_or(ApiError::NotFound)?;
+
+    let mut comments: Vec<Comment> = db::comments_by_blog_id(&state.db, blog.id)
+        .await?
+        .into_iter()
+        .map(Comment::from)
+        .collect();
+
+    // Replies to comments that are not shown publicly are left out together with them
+    loop {
+        let ids: HashSet<i64> = comments.iter().map(|c| c.id).collect();
+        let count = comments.len();
+        comments.retain(|c| c.in_reply_to_id.is_none_or(|id| ids.contains(&id)));
+        if comments.len() == count {
+            break;
+        }
+    }
+
+    let mut replies = HashMap::new();
+    for reply_to_id in comments.iter().filter_map(|c| c.in_reply_to_id) {
+        *replies.entry(reply_to_id).or_insert(0) += 1;
+    }
+    for comment in comments.iter_mut() {
+        comment.replies = replies.get(&comment.id).copied().unwrap_or(0);
+    }
+
+    Ok((blog, comments))
+}
+
+pub async fn get_comments(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetComments>,
+) -> Result<Json<CommentsResponse>, ApiError> {
+    let (blog, comments) = load_comments(&state, &query.url).await?;
+
+    Ok(Json(CommentsResponse {
+        url: blog.url,
+        total: comments.len(),
+        comments,
+    }))
+}
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetThread {
+    /// URL of the blog post to retrieve the thread for
     pub url: String,
+    /// Order of replies to the same comment
+    #[serde(default)]
+    pub sort: ThreadOrder,
+    /// Number of comments to skip, counted in the depth-first order of the thread
+    #[serde(default)]
+    pub offset: usize,
+    /// Maximum number of comments to return, all remaining comments if not set
+    pub limit: Option<usize>,
 }
 
 #[derive(Debug, Serialize, JsonSchema)]
-pub struct CommentResponse {
+pub struct ThreadResponse {
+    /// URL of the blog post
     pub url: String,
+    /// Total number of notes in the thread
+    pub total: usize,
+    /// The requested page of the thread, with replies nested under the comments they reply to
+    pub comments: Vec<ThreadComment>,
+}
+
+pub async fn get_thread(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<GetThread>,
+) -> Result<Json<ThreadResponse>, ApiError> {
+    let (blog, comments) = load_comments(&state, &query.url).await?;
+    let thread = state
+        .storage
+        .thread_by_blog_id(
+            blog.id.into(),
+            query.sort.into(),
+            ThreadPage {
+                offset: query.offset,
+                limit: query.limit,
+            },
+        )
+        .await?;
+
+    // The thread only carries the notes, the authors come with the comments
+    let mut comments: HashMap<i64, Comment> = comments.into_iter().map(|c| (c.id, c)).collect();
+    let page = thread
+        .notes
+        .into_iter()
+        .filter_map(|n| comments.remove(&n.note.id.into()).map(|c| (c, n.depth)))
+        .collect();
+
+    Ok(Json(ThreadResponse {
+        url: blog.url,
+        total: thread.total,
+        comments: thread::nest(page),
+    }))
+}
+
+/// Maximum number of URLs that can be queried in a single comment count request
+const MAX_COUNT_URLS: usize = 100;
+
+#[derive(Debug, Deserialize, JsonSchema)]
+pub struct GetCommentCounts {
+    /// URLs of the blog posts to retrieve the counts for
+    pub urls: Vec<String>,
+}
+
+/// Counts of interactions with a single blog post
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommentCounts {
+    /// Number of replies across the whole thread
+    pub replies: i64,
+    /// Total number of likes across the whole thread
+    pub likes: i64,
+    /// Total number of reposts across the whole thread
+    pub reposts: i64,
 }
 
-pub async fn get_comments(Json(request): Json<GetComments>) -> impl IntoApiResponse {
-    Json(CommentResponse { url: request.url })
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct CommentCountsResponse {
+    /// Counts for each known blog post, keyed by the blog post URL
+    pub counts: HashMap<String, CommentCounts>,
+    /// Requested URLs that fediscus does not know about
+    pub unknown: Vec<String>,
+}
+
+pub async fn get_comment_counts(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GetCommentCounts>,
+) -> Result<Json<CommentCountsResponse>, ApiError> {
+    if request.urls.len() > MAX_COUNT_URLS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {} URLs can be requested at once",
+            MAX_COUNT_URLS
+        )));
+    }
+
+    let counts: HashMap<String, CommentCounts> = db::counts_by_blog_urls(&state.db, &request.urls)
+        .await?
+        .into_iter()
+        .map(|row| {
+            (
+                row.url,
+                CommentCounts {
+                    replies: row.replies,
+                    likes: row.likes,
+                    reposts: row.reposts,
+                },
+            )
+        })
+        .collect();
+
+    let unknown = request
+        .urls
+        .into_iter()
+        .filter(|url| !counts.contains_key(url))
+        .collect();
+
+    Ok(Json(CommentCountsResponse { counts, unknown }))
 }
@@ -0,0 +1,69 @@
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::handlers::Comment;
+
+/// Order of replies to the same comment
+#[derive(Debug, Clone, Copy, Default, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ThreadOrder {
+    #[default]
+    Oldest,
+    Newest,
+    MostLiked,
+}
+
+impl From<ThreadOrder> for fediscus_activitypub::ThreadOrder {
+    fn from(order: ThreadOrder) -> Self {
+        match order {
+            ThreadOrder::Oldest => Self::Oldest,
+            ThreadOrder::Newest => Self::Newest,
+            ThreadOrder::MostLiked => Self::MostLiked,
+        }
+    }
+}
+
+/// A comment with all its replies
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct ThreadComment {
+    #[serde(flatten)]
+    pub comment: Comment,
+    /// Depth of the comment in the reply tree, root notes have depth 0
+    pub depth: usize,
+    /// Replies to this comment that are part of the current page
+    pub children: Vec<ThreadComment>,
+}
+
+/// Turns a depth-first walk of (a part of) the reply tree back into a tree. Comments whose
+/// parent is not part of `flattened` end up at the top level.
+pub fn nest(flattened: Vec<(Comment, usize)>) -> Vec<ThreadComment> {
+    fn attach(
+        comment: ThreadComment,
+        stack: &mut [ThreadComment],
+        result: &mut Vec<ThreadComment>,
+    ) {
+        match stack.last_mut() {
+            Some(parent) => parent.children.push(comment),
+            None => result.push(comment),
+        }
+    }
+
+    let mut result = Vec::new();
+    let mut stack: Vec<ThreadComment> = Vec::new();
+    for (comment, depth) in flattened {
+        while stack.last().is_some_and(|top| top.depth >= depth) {
+            if let Some(done) = stack.pop() {
+                attach(done, &mut stack, &mut result);
+            }
+        }
+        stack.push(ThreadComment {
+            comment,
+            depth,
+            children: Vec::new(),
+        });
+    }
+    while let Some(done) = stack.pop() {
+        attach(done, &mut stack, &mut result);
+    }
+    result
+}
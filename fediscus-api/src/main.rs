@@ -1,6 +1,9 @@
-use anyhow::Error;
+use anyhow::{anyhow, Error};
+use sqlx::sqlite::SqlitePoolOptions;
+use tracing::info;
 
 mod config;
+mod db;
 mod http_server;
 
 use crate::config::Config;
@@ -13,8 +16,39 @@ async fn main() -> Result<(), Error> {
 
     let config = Config::load()?;
 
-    http_server::HttpServer::new(config.http_server)
-        .run()
+    let db = SqlitePoolOptions::new()
+        .max_connections(config.database.pool_size)
+        .connect(&config.database.url)
         .await?;
-    Ok(())
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args
+        .iter()
+        .map(String::as_str)
+        .collect::<Vec<_>>()
+        .as_slice()
+    {
+        [] => {
+            http_server::HttpServer::new(config.http_server, db)
+                .run()
+                .await
+        }
+        ["create-token", name] => {
+            let token = http_server::generate_token();
+            db::new_admin_token(&db, name, &http_server::hash_token(&token)).await?;
+            // Only the hash is stored, so this is the only chance to see the token
+            println!("{}", token);
+            Ok(())
+        }
+        ["revoke-token", name] => {
+            if !db::delete_admin_token(&db, name).await? {
+                return Err(anyhow!("No admin token named '{}'", name));
+            }
+            info!("Revoked admin token {}", name);
+            Ok(())
+        }
+        _ => Err(anyhow!(
+            "Usage: fediscus-api [create-token <name> | revoke-token <name>]"
+        )),
+    }
 }
@@ -0,0 +1,13 @@
+/// Normalizes the domain so that it can be compared with the host of a URL
+pub fn normalize_domain(domain: &str) -> String {
+    domain.trim().trim_end_matches('.').to_lowercase()
+}
+
+/// Returns whether the host is the (normalized) domain or one of its subdomains
+pub fn is_subdomain(host: &str, domain: &str) -> bool {
+    let host = host.to_lowercase();
+    host == domain
+        || host
+            .strip_suffix(domain)
+            .is_some_and(|prefix| prefix.ends_with('.'))
+}
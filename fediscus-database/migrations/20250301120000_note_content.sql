@@ -0,0 +1,10 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+ALTER TABLE notes ADD COLUMN content TEXT NOT NULL DEFAULT '';
+ALTER TABLE notes ADD COLUMN summary TEXT NULL; -- content warning
+ALTER TABLE notes ADD COLUMN published DATETIME NULL; -- as reported by the author's instance
+ALTER TABLE notes ADD COLUMN language VARCHAR(16) NULL;
+ALTER TABLE notes ADD COLUMN url VARCHAR(255) NULL; -- human-readable URL of the note
+ALTER TABLE notes ADD COLUMN tags TEXT NOT NULL DEFAULT '[]'; -- JSON array of tags
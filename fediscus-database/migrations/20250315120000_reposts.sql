@@ -0,0 +1,16 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+CREATE TABLE reposts (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    note_id INTEGER NOT NULL, -- reposted note
+    account_id INTEGER NOT NULL, -- who reposted the note
+    uri VARCHAR(255) NOT NULL, -- the Announce activity
+
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    UNIQUE (note_id, account_id)
+);
+CREATE INDEX reposts_uri ON reposts(uri);
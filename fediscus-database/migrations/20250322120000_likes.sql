@@ -0,0 +1,19 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+CREATE TABLE likes (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    note_id INTEGER NOT NULL, -- liked note
+    account_id INTEGER NOT NULL, -- who liked the note
+    uri VARCHAR(255) NOT NULL, -- the Like activity
+
+    FOREIGN KEY (note_id) REFERENCES notes(id) ON DELETE CASCADE,
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE,
+    UNIQUE (note_id, account_id)
+);
+CREATE INDEX likes_uri ON likes(uri);
+
+-- Likes were only counted so far, there's no record of who liked what, so start over
+UPDATE notes SET likes = 0;
@@ -0,0 +1,6 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- Deleted notes are kept as tombstones so that replies to them stay in the thread
+ALTER TABLE notes ADD COLUMN deleted_at DATETIME NULL;
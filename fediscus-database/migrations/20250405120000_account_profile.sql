@@ -0,0 +1,8 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+ALTER TABLE accounts ADD COLUMN display_name VARCHAR(255) NULL;
+ALTER TABLE accounts ADD COLUMN avatar_url VARCHAR(255) NULL;
+ALTER TABLE accounts ADD COLUMN url VARCHAR(255) NULL; -- profile page of the account
+ALTER TABLE accounts ADD COLUMN bot BOOLEAN NOT NULL DEFAULT FALSE;
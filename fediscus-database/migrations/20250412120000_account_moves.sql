@@ -0,0 +1,6 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- The account the account has migrated to
+ALTER TABLE accounts ADD COLUMN moved_to_id INTEGER NULL REFERENCES accounts(id) ON DELETE SET NULL;
@@ -0,0 +1,14 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+CREATE TABLE outbox (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    account_id INTEGER NOT NULL, -- who sent the activity
+    uri VARCHAR(255) NOT NULL UNIQUE, -- ID of the activity
+    activity TEXT NOT NULL, -- the activity as it was sent, as JSON
+
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+CREATE INDEX outbox_account_id ON outbox(account_id);
@@ -0,0 +1,23 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- Instances we don't accept anything from, including their subdomains
+CREATE TABLE domain_blocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    domain VARCHAR(255) NOT NULL,
+    reason TEXT NULL,
+
+    UNIQUE (domain)
+);
+
+-- Individual actors we don't accept anything from, they don't need to be known to us
+CREATE TABLE actor_blocks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    uri VARCHAR(255) NOT NULL,
+    reason TEXT NULL,
+
+    UNIQUE (uri)
+);
@@ -0,0 +1,11 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- How replies to the blog post are moderated: 'auto_approve', 'approve_known' or 'hold_all'
+ALTER TABLE blogs ADD COLUMN moderation_policy VARCHAR(16) NOT NULL DEFAULT 'auto_approve';
+
+-- One of 'pending', 'approved', 'rejected' or 'hidden', only approved notes are shown publicly
+ALTER TABLE notes ADD COLUMN moderation_state VARCHAR(16) NOT NULL DEFAULT 'approved';
+
+CREATE INDEX notes_moderation_state ON notes(moderation_state);
@@ -0,0 +1,30 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- Bearer tokens granting access to the admin API, only the SHA-256 hash of the token is stored
+CREATE TABLE admin_tokens (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    token_hash VARCHAR(64) NOT NULL,
+    last_used_at DATETIME NULL,
+
+    UNIQUE (name),
+    UNIQUE (token_hash)
+);
+
+-- Jobs requested through the admin API and executed by fediscus-activitypub
+CREATE TABLE jobs (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    -- 'backfill' (target is a blog URL) or 'refresh_account' (target is an account URI)
+    kind VARCHAR(32) NOT NULL,
+    target VARCHAR(255) NOT NULL,
+    started_at DATETIME NULL,
+    finished_at DATETIME NULL,
+    -- Set when the job has failed
+    error TEXT NULL
+);
+
+CREATE INDEX jobs_started_at ON jobs(started_at);
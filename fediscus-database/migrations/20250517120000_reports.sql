@@ -0,0 +1,17 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- Flag activities sent to us by other instances, kept for review by the admin
+CREATE TABLE reports (
+    id INTEGER PRIMARY KEY AUTOINCREMENT NOT NULL,
+    created_at DATETIME DEFAULT (DATETIME('now')) NOT NULL,
+    account_id INTEGER NOT NULL, -- who sent the report
+    uri VARCHAR(255) NOT NULL UNIQUE, -- ID of the Flag activity
+    content TEXT NULL, -- the reporter's comment
+    objects TEXT NOT NULL, -- the reported accounts and notes, as a JSON array of URIs
+    resolved_at DATETIME NULL,
+
+    FOREIGN KEY (account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+CREATE INDEX reports_resolved_at ON reports(resolved_at);
@@ -0,0 +1,6 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- When the account was created on its instance, if the instance tells us
+ALTER TABLE accounts ADD COLUMN published DATETIME NULL;
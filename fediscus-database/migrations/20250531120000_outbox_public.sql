@@ -0,0 +1,10 @@
+-- SPDX-FileCopyrightText: 2025 Daniel Vrátil <me@dvratil.cz>
+--
+-- SPDX-License-Identifier: MIT
+
+-- Whether the activity is listed in the outbox, activities addressed to a single account are
+-- kept only for the record
+ALTER TABLE outbox ADD COLUMN public BOOLEAN NOT NULL DEFAULT FALSE;
+UPDATE outbox SET public = TRUE WHERE json_extract(activity, '$.type') = 'Create';
+DROP INDEX outbox_account_id;
+CREATE INDEX outbox_account_id_public ON outbox(account_id, public);